@@ -0,0 +1,514 @@
+//! Coefficients of fractional parentage of `j^n` and `l^n` configurations.
+//!
+//! The states of `n` equivalent particles are expanded in terms of the
+//! states of `n − 1` particles (the parents) coupled to one more particle,
+//!
+//! ```text
+//! |x^n α⟩ = Σ[β] (x^n α{|x^(n−1) β) |x^(n−1) β, x; α⟩
+//! ```
+//!
+//! Up to half filling, the coefficients are obtained recursively as the
+//! columns of the antisymmetrizer in the basis of parents, which is split
+//! into states of definite seniority using the pairing interaction.  States
+//! with the same seniority and angular momenta (e.g. in the `f` shell, where
+//! Racah's additional labels would be needed, or in `(9/2)^4`) are
+//! orthogonalized in the order of their parents, which fails if the result
+//! cannot be represented exactly.  Beyond half filling, the states are the
+//! particle–hole conjugates of the states of `dimension − n` particles, and
+//! the coefficients follow from those of the holes.
+//!
+//! The sign of each state up to half filling is chosen such that its first
+//! nonvanishing coefficient is positive.
+
+use std::{error, fmt};
+use std::cmp::Ordering;
+use rug::Rational;
+use super::{SignedSqrt, Wigner6j};
+use super::internal::{get_triangular_tjs, phase, triangle_condition};
+use super::slater::{Parentage, ShellTerm};
+
+/// Single-particle orbit of equivalent particles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Orbit {
+    /// Orbit of angular momentum `j`, coupled in `jj` coupling
+    J(i32),
+    /// Orbit of orbital angular momentum `l` and spin `1/2`, coupled in `LS`
+    /// coupling
+    L(i32),
+}
+
+impl Orbit {
+    /// Angular momenta of a single particle, one for each space that is
+    /// coupled separately.
+    fn spaces(self) -> Vec<i32> {
+        match self {
+            Orbit::J(tj) => vec![tj],
+            Orbit::L(tl) => vec![tl, 1],
+        }
+    }
+
+    /// Number of single-particle states.
+    pub fn dimension(self) -> i32 {
+        match self {
+            Orbit::J(tj) => tj + 1,
+            Orbit::L(tl) => 2 * (tl + 1),
+        }
+    }
+}
+
+/// State of `n` equivalent particles.
+///
+/// `tj` is the total angular momentum for `Orbit::J` and the total orbital
+/// angular momentum for `Orbit::L`, in which case `ts` is the total spin
+/// (otherwise it is zero).  `index` distinguishes states that share the
+/// other labels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Term {
+    pub seniority: i32,
+    pub tj: i32,
+    pub ts: i32,
+    pub index: usize,
+}
+
+impl Term {
+    fn labels(self) -> [i32; 2] {
+        [self.tj, self.ts]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CfpError {
+    /// The number of particles is not between `1` and the dimension of the
+    /// orbit.
+    Occupancy,
+    /// States sharing the given seniority and angular momenta could not be
+    /// orthogonalized exactly.
+    Inexact {
+        n: i32,
+        term: Term,
+    },
+}
+
+impl fmt::Display for CfpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CfpError::Occupancy => write!(f, "invalid number of particles"),
+            CfpError::Inexact { n, term } =>
+                write!(f, "cannot orthogonalize states {:?} of {} particles \
+                           exactly", term, n),
+        }
+    }
+}
+
+impl error::Error for CfpError {}
+
+/// Coefficients of fractional parentage of `n` equivalent particles.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CfpTable {
+    pub orbit: Orbit,
+    pub n: i32,
+    /// States of `n` particles.
+    pub terms: Vec<Term>,
+    /// States of `n − 1` particles.
+    pub parents: Vec<Term>,
+    /// `cfps[i][j]` is the coefficient of `parents[j]` in `terms[i]`.
+    pub cfps: Vec<Vec<SignedSqrt>>,
+}
+
+impl CfpTable {
+    /// Index of a term, if it exists.
+    pub fn find(&self, term: Term) -> Option<usize> {
+        self.terms.iter().position(|&t| t == term)
+    }
+
+    /// Terms in the form expected by `slater::shell_direct`, for an
+    /// `Orbit::L`.
+    pub fn shell_terms(&self) -> Vec<ShellTerm> {
+        self.terms.iter().zip(&self.cfps).map(|(term, cfps)| ShellTerm {
+            tl: term.tj,
+            ts: term.ts,
+            parents: self.parents.iter().zip(cfps).enumerate()
+                .filter(|&(_, (_, cfp))| cfp.sign() != Ordering::Equal)
+                .map(|(i, (parent, cfp))| Parentage {
+                    parent: i,
+                    tl: parent.tj,
+                    ts: parent.ts,
+                    cfp: cfp.clone(),
+                })
+                .collect(),
+        }).collect()
+    }
+}
+
+/// Tables of coefficients of fractional parentage for `1 ≤ n ≤ n_max`
+/// particles.
+pub fn cfp_tables(orbit: Orbit, n_max: i32) -> Result<Vec<CfpTable>, CfpError> {
+    let dimension = orbit.dimension();
+    if n_max < 1 || n_max > dimension {
+        return Err(CfpError::Occupancy);
+    }
+    let half = (dimension + 1) / 2;
+    let mut tables = recursive_tables(orbit, n_max.min(half))?;
+    for n in half + 1 .. n_max + 1 {
+        let table = conjugate(&tables[(dimension - n) as usize], n);
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+/// Table of coefficients of fractional parentage for `n` particles.
+///
+/// The tables for fewer particles are rebuilt on every call; use
+/// `cfp_tables` to obtain several tables at once.
+pub fn cfp_table(orbit: Orbit, n: i32) -> Result<CfpTable, CfpError> {
+    let dimension = orbit.dimension();
+    if n > (dimension + 1) / 2 && n <= dimension {
+        return cfp_table(orbit, dimension + 1 - n)
+            .map(|hole| conjugate(&hole, n));
+    }
+    cfp_tables(orbit, n).map(|mut tables| tables.pop().expect("table"))
+}
+
+/// Tables for `1 ≤ n ≤ n_max` particles, each obtained from the previous
+/// one.
+fn recursive_tables(orbit: Orbit, n_max: i32) -> Result<Vec<CfpTable>, CfpError> {
+    let spaces = orbit.spaces();
+    let single = Term {
+        seniority: 1,
+        tj: spaces[0],
+        ts: spaces.get(1).cloned().unwrap_or(0),
+        index: 0,
+    };
+    let mut tables = vec![CfpTable {
+        orbit,
+        n: 1,
+        terms: vec![single],
+        parents: vec![Term::default()],
+        cfps: vec![vec![1.into()]],
+    }];
+    for _ in 1 .. n_max {
+        let table = next_table(tables.last().expect("previous table"))?;
+        tables.push(table);
+    }
+    Ok(tables)
+}
+
+/// Rational number as a signed square root.
+fn rational(c: Rational) -> SignedSqrt {
+    SignedSqrt(c.clone() * c.abs())
+}
+
+/// Eigenvalue of the pairing interaction, normalized to `1` for a pair of
+/// seniority zero.
+fn pairing_energy(dimension: i32, n: i32, v: i32) -> Rational {
+    Rational::from(((n - v) * (dimension + 2 - n - v), 2 * dimension))
+}
+
+/// Overlap `⟨((g x) p x) t|((g x) q x) t⟩` with the last two particles
+/// exchanged, for each space.
+fn exchange(
+    spaces: &[i32],
+    g: [i32; 2],
+    p: [i32; 2],
+    q: [i32; 2],
+    t: [i32; 2],
+) -> SignedSqrt
+{
+    spaces.iter().enumerate().fold(SignedSqrt::from(1), |acc, (i, &tx)| {
+        acc * phase((2 * tx + p[i] + q[i]) / 2)
+            * SignedSqrt(Rational::from((p[i] + 1) * (q[i] + 1)))
+            * Wigner6j {
+                tj1: g[i],
+                tj2: tx,
+                tj3: p[i],
+                tj4: t[i],
+                tj5: tx,
+                tj6: q[i],
+            }.value()
+    })
+}
+
+/// Table for `n` particles from the table for `m = dimension + 1 − n`
+/// particles, by particle–hole conjugation:
+///
+/// ```text
+/// (x^n ᾱ{|x^(n−1) β̄) = (−1)^(n − 1 + x + J_β − J_α + (v_α + v_β − 1)/2)
+///                      √(m [J_β] / (n [J_α])) (x^m β{|x^(m−1) α)
+/// ```
+///
+/// where `[J] = 2 J + 1`, and the phase and `[J]` are taken for each space
+/// that is coupled separately.  The factor `(−1)^(n − 1)` makes the
+/// conjugated states agree with those of the recursion at half filling.
+fn conjugate(hole: &CfpTable, n: i32) -> CfpTable {
+    let spaces = hole.orbit.spaces();
+    let weight = |term: Term| {
+        (0 .. spaces.len()).map(|i| term.labels()[i] + 1).product::<i32>()
+    };
+    let cfps = hole.parents.iter().enumerate().map(|(i, &a)| {
+        hole.terms.iter().zip(&hole.cfps).map(|(&b, row)| {
+            if row[i].sign() == Ordering::Equal {
+                return Default::default();
+            }
+            let p: i32 = spaces.iter().enumerate()
+                .map(|(k, &tx)| tx + b.labels()[k] - a.labels()[k])
+                .sum();
+            phase(n - 1 + (p + a.seniority + b.seniority - 1) / 2)
+                * SignedSqrt(Rational::from((hole.n * weight(b), n * weight(a))))
+                * row[i].clone()
+        }).collect()
+    }).collect();
+    CfpTable {
+        orbit: hole.orbit,
+        n,
+        terms: hole.parents.clone(),
+        parents: hole.terms.clone(),
+        cfps,
+    }
+}
+
+fn next_table(prev: &CfpTable) -> Result<CfpTable, CfpError> {
+    let orbit = prev.orbit;
+    let n = prev.n + 1;
+    let spaces = orbit.spaces();
+    let couples = |parent: Term, t: [i32; 2]| {
+        spaces.iter().enumerate()
+            .all(|(i, &tx)| triangle_condition(parent.labels()[i], tx, t[i]))
+    };
+    // total angular momenta that can be reached
+    let ts = spaces.get(1).cloned().unwrap_or(0);
+    let mut totals = Vec::new();
+    for parent in &prev.terms {
+        for t0 in get_triangular_tjs(parent.tj + spaces[0], parent.tj, spaces[0]) {
+            for t1 in get_triangular_tjs(parent.ts + ts, parent.ts, ts) {
+                totals.push([t0, t1]);
+            }
+        }
+    }
+    totals.sort();
+    totals.dedup();
+    let mut states: Vec<(Term, Vec<(usize, SignedSqrt)>)> = Vec::new();
+    for t in totals {
+        let parents: Vec<usize> = (0 .. prev.terms.len())
+            .filter(|&i| couples(prev.terms[i], t))
+            .collect();
+        for (term, u) in block(prev, n, &spaces, t, &parents)? {
+            states.push((term, u.into_iter().zip(parents.iter().cloned())
+                         .map(|(c, i)| (i, c)).collect()));
+        }
+    }
+    states.sort_by_key(|&(term, _)| term);
+    let terms: Vec<Term> = states.iter().map(|&(term, _)| term).collect();
+    let cfps = states.into_iter().map(|(_, u)| {
+        let mut row = vec![SignedSqrt::default(); prev.terms.len()];
+        for (i, c) in u {
+            row[i] = c;
+        }
+        row
+    }).collect();
+    Ok(CfpTable {
+        orbit,
+        n,
+        terms,
+        parents: prev.terms.clone(),
+        cfps,
+    })
+}
+
+/// States of `n` particles with total angular momenta `t`, given as
+/// coefficients of the listed parents.
+fn block(
+    prev: &CfpTable,
+    n: i32,
+    spaces: &[i32],
+    t: [i32; 2],
+    parents: &[usize],
+) -> Result<Vec<(Term, Vec<SignedSqrt>)>, CfpError>
+{
+    let d = parents.len();
+    // antisymmetrizer (1 − (n − 1) P(n − 1, n)) / n
+    let mut a = vec![vec![Surd::default(); d]; d];
+    for (i, &p) in parents.iter().enumerate() {
+        for (j, &q) in parents.iter().enumerate() {
+            let mut x = Surd::default();
+            if i == j {
+                x.add(rational(Rational::from((1, n))));
+            }
+            for (g, grandparent) in prev.parents.iter().enumerate() {
+                let (cp, cq) = (&prev.cfps[p][g], &prev.cfps[q][g]);
+                if cp.sign() == Ordering::Equal || cq.sign() == Ordering::Equal {
+                    continue;
+                }
+                let r = exchange(spaces, grandparent.labels(),
+                                 prev.terms[p].labels(), prev.terms[q].labels(), t);
+                x.add(rational(Rational::from((1 - n, n)))
+                      * cp.clone() * cq.clone() * r);
+            }
+            a[i][j] = x;
+        }
+    }
+    let dimension = prev.orbit.dimension();
+    let seniorities: Vec<i32> = (0 .. n + 1)
+        .filter(|&v| (n - v) % 2 == 0 && v <= dimension - n)
+        .collect();
+    let projectors: Vec<(i32, Matrix)> = if n == 2 {
+        let v = if t == [0, 0] { 0 } else { 2 };
+        vec![(v, a)]
+    } else {
+        // pairing interaction n / (n − 2) A D A, where D holds the energies
+        // of the parents
+        let mut ad = a.clone();
+        for row in &mut ad {
+            for (x, &p) in row.iter_mut().zip(parents) {
+                let e = pairing_energy(dimension, n - 1, prev.terms[p].seniority)
+                    * Rational::from((n, n - 2));
+                *x = x.scale(&e);
+            }
+        }
+        let h = product(&ad, &a);
+        seniorities.iter().map(|&v| {
+            let e = pairing_energy(dimension, n, v);
+            let mut m = a.clone();
+            for &w in seniorities.iter().filter(|&&w| w != v) {
+                let f = pairing_energy(dimension, n, w);
+                let denom = Rational::from(&e - &f).recip();
+                let shifted: Matrix = h.iter().zip(&a).map(|(hr, ar)| {
+                    hr.iter().zip(ar).map(|(hx, ax)| {
+                        hx.plus(&ax.scale(&-f.clone())).scale(&denom)
+                    }).collect()
+                }).collect();
+                m = product(&m, &shifted);
+            }
+            (v, m)
+        }).collect()
+    };
+    let mut states = Vec::new();
+    for (v, m) in projectors {
+        let mut us: Vec<Vec<Surd>> = Vec::new();
+        for k in 0 .. d {
+            // w = m e_k − Σ u u[k], whose norm is m[k][k] − Σ u[k]²
+            let mut norm = m[k][k].clone();
+            for u in &us {
+                norm = norm.plus(&u[k].times(&u[k]).scale(&Rational::from(-1)));
+            }
+            if norm.is_zero() {
+                continue;
+            }
+            let term = Term {
+                seniority: v,
+                tj: t[0],
+                ts: t[1],
+                index: us.len(),
+            };
+            let inexact = CfpError::Inexact { n, term };
+            // the norm must be a positive rational for 1 / √norm to be
+            // representable
+            let norm = norm.to_signed_sqrt().ok_or_else(|| inexact.clone())?;
+            if norm.sign() != Ordering::Greater {
+                return Err(inexact);
+            }
+            let (numer, denom) = norm.signed_sq().into_numer_denom();
+            if !numer.is_perfect_square() || !denom.is_perfect_square() {
+                return Err(inexact);
+            }
+            let inv = SignedSqrt(Rational::from((denom.sqrt(), numer.sqrt())));
+            let w: Vec<Surd> = (0 .. d).map(|i| {
+                us.iter().fold(m[i][k].clone(), |acc, u| {
+                    acc.plus(&u[i].times(&u[k]).scale(&Rational::from(-1)))
+                })
+            }).collect();
+            us.push(w.iter().map(|x| x.scale_sqrt(&inv)).collect());
+        }
+        for (index, u) in us.into_iter().enumerate() {
+            let term = Term { seniority: v, tj: t[0], ts: t[1], index };
+            let u = u.iter().map(|x| x.to_signed_sqrt())
+                .collect::<Option<Vec<_>>>()
+                .ok_or(CfpError::Inexact { n, term })?;
+            states.push((term, u));
+        }
+    }
+    Ok(states)
+}
+
+type Matrix = Vec<Vec<Surd>>;
+
+fn product(a: &Matrix, b: &Matrix) -> Matrix {
+    let d = b.first().map_or(0, |row| row.len());
+    a.iter().map(|row| {
+        (0 .. d).map(|j| {
+            row.iter().zip(b).fold(Surd::default(), |acc, (x, brow)| {
+                if x.is_zero() || brow[j].is_zero() {
+                    acc
+                } else {
+                    acc.plus(&x.times(&brow[j]))
+                }
+            })
+        }).collect()
+    }).collect()
+}
+
+/// Sum of signed square roots of rationals whose radicands are not rational
+/// multiples of each other.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Surd(Vec<SignedSqrt>);
+
+impl Surd {
+    fn add(&mut self, x: SignedSqrt) {
+        if x.sign() == Ordering::Equal {
+            return;
+        }
+        for (i, y) in self.0.iter_mut().enumerate() {
+            if let Some(sum) = y.clone().checked_add(x.clone()) {
+                if sum.sign() == Ordering::Equal {
+                    self.0.remove(i);
+                } else {
+                    *y = sum;
+                }
+                return;
+            }
+        }
+        self.0.push(x);
+    }
+
+    fn plus(&self, other: &Surd) -> Surd {
+        let mut sum = self.clone();
+        for x in &other.0 {
+            sum.add(x.clone());
+        }
+        sum
+    }
+
+    fn times(&self, other: &Surd) -> Surd {
+        let mut product = Surd::default();
+        for x in &self.0 {
+            for y in &other.0 {
+                product.add(x.clone() * y.clone());
+            }
+        }
+        product
+    }
+
+    /// Multiply by a rational number.
+    fn scale(&self, c: &Rational) -> Surd {
+        self.scale_sqrt(&rational(c.clone()))
+    }
+
+    fn scale_sqrt(&self, c: &SignedSqrt) -> Surd {
+        Surd(if c.sign() == Ordering::Equal {
+            Vec::new()
+        } else {
+            self.0.iter().map(|x| x.clone() * c.clone()).collect()
+        })
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn to_signed_sqrt(&self) -> Option<SignedSqrt> {
+        match self.0.len() {
+            0 => Some(Default::default()),
+            1 => Some(self.0[0].clone()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Decomposition of products of multiplets into irreducible components.
+//!
+//! The components of a multiplet `j` are stored in a vector indexed by `m`
+//! in increasing order, and a state (or operator) of a pair of multiplets in
+//! a matrix indexed by `[m1][m2]`.
+
+use std::collections::BTreeMap;
+use super::{ClebschGordan, SignedSqrt, Wigner3jm};
+use super::internal::{get_tms, get_triangular_tjs, phase, sum_radicals,
+                      wigner_3jm_selection_rules};
+use super::regge::{CanonicalRegge3jm, Regge3jm};
+use super::table::build_3jm_table;
+use super::tensor::Reduced;
+
+/// Values of components.
+pub trait Component: Reduced + Clone + Default {
+    /// Sum the terms, failing if the result cannot be represented.
+    fn sum(terms: Vec<Self>) -> Option<Self>;
+}
+
+impl Component for SignedSqrt {
+    fn sum(terms: Vec<Self>) -> Option<Self> {
+        sum_radicals(terms)
+    }
+}
+
+impl Component for f64 {
+    fn sum(terms: Vec<Self>) -> Option<Self> {
+        Some(terms.into_iter().sum())
+    }
+}
+
+/// Source of Clebsch–Gordan coefficients.
+pub trait Coefficients<T> {
+    /// Multiply `x` by the coefficient.
+    fn weigh(&self, cg: ClebschGordan, x: T) -> T;
+}
+
+/// Coefficients computed exactly by `ClebschGordan::value`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Exact;
+
+impl<T: Reduced> Coefficients<T> for Exact {
+    fn weigh(&self, cg: ClebschGordan, x: T) -> T {
+        x.scale(cg.value())
+    }
+}
+
+/// Table of Clebsch–Gordan coefficients in floating point, for angular
+/// momenta up to `tj_max`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClebschGordanTable {
+    tj_max: i32,
+    values: Vec<f64>,
+}
+
+impl ClebschGordanTable {
+    /// See `build_table` for the meaning of `threads`.
+    pub fn new(tj_max: i32, threads: usize) -> Self {
+        let values = build_3jm_table(tj_max, threads, &|_, _| {});
+        Self {
+            tj_max,
+            values: values.into_iter().map(f64::from).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn tj_max(&self) -> i32 {
+        self.tj_max
+    }
+
+    /// Look up a coefficient.  Panics if an angular momentum exceeds
+    /// `tj_max`.
+    pub fn get(&self, cg: ClebschGordan) -> f64 {
+        assert!([cg.tj1, cg.tj2, cg.tj12].iter().all(|&tj| tj <= self.tj_max),
+                "angular momentum exceeds tj_max");
+        let w3jm = Wigner3jm::from(cg);
+        if !wigner_3jm_selection_rules(w3jm) {
+            return 0.0;
+        }
+        let (regge, regge_phase): (CanonicalRegge3jm<u32>, _) =
+            Regge3jm::from(w3jm).checked_canonicalize()
+            .expect("key fits into u32");
+        let c = phase((cg.tj1 - cg.tj2 + cg.tm12) / 2) * regge_phase;
+        f64::from(c) * ((cg.tj12 + 1) as f64).sqrt() * self.values[regge.index()]
+    }
+}
+
+impl Coefficients<f64> for ClebschGordanTable {
+    fn weigh(&self, cg: ClebschGordan, x: f64) -> f64 {
+        x * self.get(cg)
+    }
+}
+
+/// Product state of `a` in the multiplet `j1` and `b` in `j2`.
+pub fn product<T: Component>(a: &[T], b: &[T]) -> Vec<Vec<T>> {
+    a.iter().map(|x| {
+        b.iter().map(|y| x.clone() * y.clone()).collect()
+    }).collect()
+}
+
+/// Decompose a state of the multiplets `j1` and `j2` into its components
+/// `|(j1 j2) j m⟩`, keyed by `tj`.
+///
+/// Returns `None` if a component cannot be represented.
+pub fn decompose<T: Component, C: Coefficients<T>>(
+    coefficients: &C,
+    tj1: i32,
+    tj2: i32,
+    state: &[Vec<T>],
+) -> Option<BTreeMap<i32, Vec<T>>>
+{
+    assert_eq!(state.len() as i32, tj1 + 1, "wrong number of rows");
+    let mut components = BTreeMap::new();
+    for tj in get_triangular_tjs(tj1 + tj2, tj1, tj2) {
+        let mut vector = Vec::new();
+        for tm in get_tms(tj) {
+            let mut terms = Vec::new();
+            for (row, tm1) in state.iter().zip(get_tms(tj1)) {
+                assert_eq!(row.len() as i32, tj2 + 1, "wrong number of columns");
+                let tm2 = tm - tm1;
+                if tm2.abs() > tj2 {
+                    continue;
+                }
+                let cg = ClebschGordan { tj1, tm1, tj2, tm2, tj12: tj, tm12: tm };
+                terms.push(coefficients.weigh(cg, row[((tm2 + tj2) / 2) as usize].clone()));
+            }
+            vector.push(T::sum(terms)?);
+        }
+        components.insert(tj, vector);
+    }
+    Some(components)
+}
+
+/// Inverse of `decompose`: couple the components `|(j1 j2) j m⟩` back into
+/// a state of the multiplets `j1` and `j2`.  Missing `j` are treated as zero.
+///
+/// Returns `None` if an entry cannot be represented.
+pub fn couple_back<T: Component, C: Coefficients<T>>(
+    coefficients: &C,
+    tj1: i32,
+    tj2: i32,
+    components: &BTreeMap<i32, Vec<T>>,
+) -> Option<Vec<Vec<T>>>
+{
+    get_tms(tj1).map(|tm1| {
+        get_tms(tj2).map(|tm2| {
+            let tm = tm1 + tm2;
+            let mut terms = Vec::new();
+            for (&tj, vector) in components {
+                assert_eq!(vector.len() as i32, tj + 1, "wrong number of components");
+                if tm.abs() > tj {
+                    continue;
+                }
+                let cg = ClebschGordan { tj1, tm1, tj2, tm2, tj12: tj, tm12: tm };
+                terms.push(coefficients.weigh(cg, vector[((tm + tj) / 2) as usize].clone()));
+            }
+            T::sum(terms)
+        }).collect()
+    }).collect()
+}
@@ -0,0 +1,100 @@
+//! Angular coupling of Dirac spinors.
+//!
+//! The spin-angular functions `Ω_κm` are labeled by the relativistic
+//! quantum number
+//!
+//! ```text
+//! κ = (l − j) (2 j + 1)
+//! ```
+//!
+//! which is `−(l + 1)` for `j = l + 1/2` and `l` for `j = l − 1/2`.  The
+//! small component of the spinor has the orbital angular momentum of `−κ`.
+
+use rug::Rational;
+use super::{SignedSqrt, Wigner3jm};
+use super::internal::phase;
+use super::tensor::{Convention, WignerEckart};
+
+/// Relativistic angular quantum number `κ`, which is nonzero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Kappa(pub i32);
+
+impl Kappa {
+    /// The `κ` of the spinor with orbital angular momentum `l` (in the large
+    /// component) and total angular momentum `j`, or `None` if `j ≠ l ± 1/2`.
+    pub fn new(tl: i32, tj: i32) -> Option<Self> {
+        if tl < 0 || tl % 2 != 0 {
+            return None;
+        }
+        if tj == tl + 1 {
+            Some(Kappa(-(tl / 2 + 1)))
+        } else if tj == tl - 1 {
+            Some(Kappa(tl / 2))
+        } else {
+            None
+        }
+    }
+
+    /// `2 j = 2 |κ| − 1`.
+    pub fn tj(self) -> i32 {
+        2 * self.0.abs() - 1
+    }
+
+    /// `2 l` of the large component.
+    pub fn tl(self) -> i32 {
+        if self.0 > 0 {
+            2 * self.0
+        } else {
+            -2 * self.0 - 2
+        }
+    }
+
+    /// `2 l` of the small component, i.e. that of `−κ`.
+    pub fn tl_small(self) -> i32 {
+        Kappa(-self.0).tl()
+    }
+}
+
+/// Reduced matrix element `⟨κ'‖C^k‖κ⟩` of the Racah tensor between
+/// spin-angular functions, in the Edmonds convention:
+///
+/// ```text
+/// ⟨κ'‖C^k‖κ⟩ = (−1)^(j' + 1/2) √((2 j' + 1) (2 j + 1)) (j' j k; −1/2 1/2 0)
+/// ```
+///
+/// if `l' + k + l` is even, and zero otherwise (parity).  The same value is
+/// obtained with the orbital angular momenta of either component.
+pub fn racah_tensor(kappa1: Kappa, tk: i32, kappa2: Kappa) -> SignedSqrt {
+    if (kappa1.tl() + tk + kappa2.tl()) % 4 != 0 {
+        return Default::default();
+    }
+    let (tj1, tj2) = (kappa1.tj(), kappa2.tj());
+    phase((tj1 + 1) / 2)
+        * SignedSqrt(Rational::from(tj1 + 1) * Rational::from(tj2 + 1))
+        * Wigner3jm {
+            tj1,
+            tm1: -1,
+            tj2,
+            tm2: 1,
+            tj3: tk,
+            tm3: 0,
+        }.value()
+}
+
+/// Matrix element `⟨κ' m'|C^k_q|κ m⟩` between spin-angular functions.
+pub fn racah_tensor_element(
+    kappa1: Kappa,
+    tm1: i32,
+    tk: i32,
+    tq: i32,
+    kappa2: Kappa,
+    tm2: i32,
+) -> SignedSqrt
+{
+    WignerEckart {
+        tj1: kappa1.tj(),
+        tk,
+        tj2: kappa2.tj(),
+        convention: Convention::Edmonds,
+    }.matrix_element(racah_tensor(kappa1, tk, kappa2), tm1, tq, tm2)
+}
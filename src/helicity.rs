@@ -0,0 +1,110 @@
+//! Partial-wave analysis of two-body decays `J → s1 s2` in the helicity
+//! formalism of Jacob and Wick.
+
+use rug::Rational;
+use super::{ClebschGordan, SignedSqrt};
+use super::internal::{get_tms, get_triangular_tjs, phase};
+
+/// Intrinsic parities (`±1`) of the decaying particle and the two daughters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Parities {
+    pub parent: i32,
+    pub first: i32,
+    pub second: i32,
+}
+
+/// Recoupling between the helicity states `|J M; λ1 λ2⟩` and the partial
+/// waves `|J M; L S⟩` of a decay of spin `J` into particles of spins `s1`
+/// and `s2`.  If `parities` is given, only the partial waves that conserve
+/// parity, i.e. with `(−1)^L = P P1 P2`, are included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HelicityCoupling {
+    pub tj: i32,
+    pub ts1: i32,
+    pub ts2: i32,
+    pub parities: Option<Parities>,
+}
+
+impl HelicityCoupling {
+    /// Recoupling coefficient
+    ///
+    /// ```text
+    /// ⟨J M; λ1 λ2|J M; L S⟩ = √((2 L + 1) / (2 J + 1))
+    ///                         ⟨L 0 S λ|J λ⟩ ⟨s1 λ1 s2 −λ2|S λ⟩
+    /// ```
+    ///
+    /// where `λ = λ1 − λ2`.  It does not depend on `M`.
+    pub fn coefficient(
+        self,
+        tlambda1: i32,
+        tlambda2: i32,
+        tl: i32,
+        ts: i32,
+    ) -> SignedSqrt
+    {
+        let tlambda = tlambda1 - tlambda2;
+        SignedSqrt(Rational::from((tl + 1, self.tj + 1)))
+            * ClebschGordan {
+                tj1: tl,
+                tm1: 0,
+                tj2: ts,
+                tm2: tlambda,
+                tj12: self.tj,
+                tm12: tlambda,
+            }.value()
+            * ClebschGordan {
+                tj1: self.ts1,
+                tm1: tlambda1,
+                tj2: self.ts2,
+                tm2: -tlambda2,
+                tj12: ts,
+                tm12: tlambda,
+            }.value()
+    }
+
+    /// Helicities `(2 λ1, 2 λ2)` with `|λ1 − λ2| ≤ J`.
+    pub fn helicities(self) -> Vec<(i32, i32)> {
+        let mut helicities = Vec::new();
+        for tlambda1 in get_tms(self.ts1) {
+            for tlambda2 in get_tms(self.ts2) {
+                if (tlambda1 - tlambda2).abs() <= self.tj {
+                    helicities.push((tlambda1, tlambda2));
+                }
+            }
+        }
+        helicities
+    }
+
+    /// Partial waves `(2 L, 2 S)` allowed by angular momentum conservation
+    /// and, if given, parity conservation.
+    pub fn partial_waves(self) -> Vec<(i32, i32)> {
+        let mut waves = Vec::new();
+        for ts in get_triangular_tjs(self.ts1 + self.ts2, self.ts1, self.ts2) {
+            for tl in get_triangular_tjs(self.tj + ts, self.tj, ts) {
+                if tl % 2 != 0 {
+                    continue;
+                }
+                if let Some(p) = self.parities {
+                    if phase(tl / 2) != p.parent * p.first * p.second {
+                        continue;
+                    }
+                }
+                waves.push((tl, ts));
+            }
+        }
+        waves
+    }
+
+    /// Matrix of `coefficient`, with rows indexed by `helicities` and
+    /// columns by `partial_waves`.  The columns are orthonormal, and
+    /// without parity conservation the matrix is square (if `J + s1 + s2`
+    /// is an integer).
+    pub fn matrix(self) -> Vec<Vec<SignedSqrt>> {
+        let waves = self.partial_waves();
+        self.helicities().into_iter().map(|(tlambda1, tlambda2)| {
+            waves.iter().map(|&(tl, ts)| {
+                self.coefficient(tlambda1, tlambda2, tl, ts)
+            }).collect()
+        }).collect()
+    }
+}
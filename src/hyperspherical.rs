@@ -0,0 +1,84 @@
+//! Coupling of hyperspherical harmonics on the three-sphere.
+//!
+//! The hyperspherical harmonics of degree `n − 1` span the representation
+//! `(j, j)` of SO(4) ≅ SU(2) × SU(2), where `j = (n − 1) / 2`, and decompose
+//! into `l = 0, 1, …, n − 1` under the rotations of SO(3).  The states are
+//! those coupled from the two SU(2) factors,
+//!
+//! ```text
+//! |n l m⟩ = Σ[μ ν] ⟨j μ j ν|l m⟩ |j μ⟩ |j ν⟩
+//! ```
+//!
+//! which agree with the harmonics up to a phase that depends on `n`, `l`,
+//! and the convention chosen for the harmonics.
+
+use rug::Rational;
+use super::{ClebschGordan, SignedSqrt, Wigner9j};
+
+/// Clebsch–Gordan coefficient of SO(4)
+///
+/// ```text
+/// ⟨n1 l1 m1, n2 l2 m2|n l m⟩
+/// ```
+///
+/// between hyperspherical harmonics, where `n ≥ 1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HypersphericalCoupling {
+    pub n1: i32,
+    pub tl1: i32,
+    pub tm1: i32,
+    pub n2: i32,
+    pub tl2: i32,
+    pub tm2: i32,
+    pub n: i32,
+    pub tl: i32,
+    pub tm: i32,
+}
+
+impl HypersphericalCoupling {
+    /// Value, which factorizes as
+    ///
+    /// ```text
+    /// ⟨n1 l1 m1, n2 l2 m2|n l m⟩ = ⟨n1 l1, n2 l2‖n l⟩ ⟨l1 m1 l2 m2|l m⟩
+    /// ```
+    pub fn value(self) -> SignedSqrt {
+        self.reduced() * ClebschGordan {
+            tj1: self.tl1,
+            tm1: self.tm1,
+            tj2: self.tl2,
+            tm2: self.tm2,
+            tj12: self.tl,
+            tm12: self.tm,
+        }.value()
+    }
+
+    /// Isoscalar factor `⟨n1 l1, n2 l2‖n l⟩` of SO(4) ⊃ SO(3), from the
+    /// recoupling of the four SU(2) angular momenta:
+    ///
+    /// ```text
+    ///                                             ⎧j1 j1 l1⎫
+    /// ⟨n1 l1, n2 l2‖n l⟩ = (2 j + 1) √([l1] [l2]) ⎨j2 j2 l2⎬
+    ///                                             ⎩j  j  l ⎭
+    /// ```
+    ///
+    /// where `[l] = 2 l + 1`.
+    pub fn reduced(self) -> SignedSqrt {
+        if self.n1 < 1 || self.n2 < 1 || self.n < 1 {
+            return Default::default();
+        }
+        let (tj1, tj2, tj) = (self.n1 - 1, self.n2 - 1, self.n - 1);
+        SignedSqrt(Rational::from((tj + 1) * (tj + 1))
+                   * Rational::from((self.tl1 + 1) * (self.tl2 + 1)))
+            * Wigner9j {
+                tj1,
+                tj2: tj1,
+                tj3: self.tl1,
+                tj4: tj2,
+                tj5: tj2,
+                tj6: self.tl2,
+                tj7: tj,
+                tj8: tj,
+                tj9: self.tl,
+            }.value()
+    }
+}
@@ -0,0 +1,343 @@
+//! Numerical verification of standard sum rules.
+//!
+//! Each check evaluates both sides of an identity exactly for every
+//! combination of angular momenta up to `tj_max` and reports the first
+//! combination where they differ.  This is useful as a self-test of the
+//! evaluation routines.
+
+use std::{error, fmt};
+use super::SignedSqrt;
+use super::internal::{sum_radicals, triangle_condition};
+use super::moshinsky::{Brackets, MoshinskyBracket};
+use super::racah::{Expression, Factor, Linear, Var};
+
+/// Arguments for which the two sides of an identity disagree.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Counterexample {
+    pub identity: &'static str,
+    /// Values of the free variables, in the order given in the documentation
+    /// of the check.
+    pub args: Vec<i32>,
+    pub lhs: SignedSqrt,
+    pub rhs: SignedSqrt,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} fails for {:?}: {} ≠ {}",
+               self.identity, self.args,
+               f64::from(self.lhs.clone()), f64::from(self.rhs.clone()))
+    }
+}
+
+impl error::Error for Counterexample {}
+
+/// How a free variable ranges.
+#[derive(Clone, Copy)]
+enum Free {
+    /// `0 ≤ tj ≤ tj_max`
+    Tj,
+    /// `−tj ≤ tm ≤ tj` in steps of two, where `tj` is an earlier variable
+    Tm(Var),
+}
+
+/// Check an identity for all values of the free variables such that every
+/// one of `triads` satisfies the triangle condition.  The triads should be
+/// ones for which both sides vanish otherwise, so that they can be skipped
+/// as soon as they are assigned.
+fn check(
+    identity: &'static str,
+    free: &[Free],
+    tj_max: i32,
+    triads: &[[Var; 3]],
+    lhs: &Expression,
+    rhs: &Expression,
+) -> Result<(), Counterexample>
+{
+    let mut args = Vec::with_capacity(free.len());
+    check_rec(identity, free, tj_max, triads, lhs, rhs, &mut args)
+}
+
+fn check_rec(
+    identity: &'static str,
+    free: &[Free],
+    tj_max: i32,
+    triads: &[[Var; 3]],
+    lhs: &Expression,
+    rhs: &Expression,
+    args: &mut Vec<i32>,
+) -> Result<(), Counterexample>
+{
+    let last = args.len().wrapping_sub(1);
+    if triads.iter().any(|t| {
+        *t.iter().max().unwrap() == last
+            && !triangle_condition(args[t[0]], args[t[1]], args[t[2]])
+    }) {
+        return Ok(());
+    }
+    if args.len() == free.len() {
+        let values: Vec<(Var, i32)> = args.iter().cloned().enumerate().collect();
+        let lhs = lhs.evaluate(&values).expect("well-formed identity");
+        let rhs = rhs.evaluate(&values).expect("well-formed identity");
+        if lhs != rhs {
+            return Err(Counterexample {
+                identity,
+                args: args.clone(),
+                lhs,
+                rhs,
+            });
+        }
+        return Ok(());
+    }
+    let (min, max, step) = match free[args.len()] {
+        Free::Tj => (0, tj_max, 1),
+        Free::Tm(var) => (-args[var], args[var], 2),
+    };
+    let mut x = min;
+    while x <= max {
+        args.push(x);
+        check_rec(identity, free, tj_max, triads, lhs, rhs, args)?;
+        args.pop();
+        x += step;
+    }
+    Ok(())
+}
+
+fn v(var: Var) -> Linear {
+    Linear::var(var)
+}
+
+/// Check that
+///
+/// ```text
+/// Σ[m1 m2] (2 j3 + 1) (j1 j2 j3; m1 m2 m3) (j1 j2 j3'; m1 m2 m3)
+///   = δ(j3, j3')
+/// ```
+///
+/// whenever `j1 j2 j3` is a triangle.  The arguments are
+/// `tj1, tj2, tj3, tj3', tm3`.
+pub fn check_3jm_orthogonality(tj_max: i32) -> Result<(), Counterexample> {
+    let lhs = Expression {
+        sums: vec![5, 6],
+        factors: vec![
+            Factor::Dimension(v(2), 2),
+            Factor::Wigner3jm([v(0), v(5), v(1), v(6), v(2), v(4)]),
+            Factor::Wigner3jm([v(0), v(5), v(1), v(6), v(3), v(4)]),
+        ],
+    };
+    let rhs = Expression {
+        sums: vec![],
+        factors: vec![Factor::Delta(v(2), v(3))],
+    };
+    check(
+        "3-jm orthogonality",
+        &[Free::Tj, Free::Tj, Free::Tj, Free::Tj, Free::Tm(2)],
+        tj_max,
+        &[[0, 1, 2]],
+        &lhs,
+        &rhs,
+    )
+}
+
+/// Check that
+///
+/// ```text
+/// Σ[j3] (2 j3 + 1) (2 j6 + 1) {j1 j2 j3; j4 j5 j6} {j1 j2 j3; j4 j5 j6'}
+///   = δ(j6, j6')
+/// ```
+///
+/// whenever `j1 j5 j6` and `j4 j2 j6` are triangles.  The arguments are
+/// `tj1, tj2, tj4, tj5, tj6, tj6'`.
+pub fn check_6j_orthogonality(tj_max: i32) -> Result<(), Counterexample> {
+    let lhs = Expression {
+        sums: vec![6],
+        factors: vec![
+            Factor::Dimension(v(6), 2),
+            Factor::Dimension(v(4), 2),
+            Factor::Wigner6j([v(0), v(1), v(6), v(2), v(3), v(4)]),
+            Factor::Wigner6j([v(0), v(1), v(6), v(2), v(3), v(5)]),
+        ],
+    };
+    let rhs = Expression {
+        sums: vec![],
+        factors: vec![Factor::Delta(v(4), v(5))],
+    };
+    check(
+        "6-j orthogonality",
+        &[Free::Tj; 6],
+        tj_max,
+        &[[0, 3, 4], [2, 1, 4]],
+        &lhs,
+        &rhs,
+    )
+}
+
+/// Check the Racah sum rule
+///
+/// ```text
+/// Σ[x] (−1)^(p + q + x) (2 x + 1) {a b x; c d p} {a b x; d c q}
+///   = {a c q; b d p}
+/// ```
+///
+/// The arguments are `ta, tb, tc, td, tp, tq`.
+pub fn check_racah_sum_rule(tj_max: i32) -> Result<(), Counterexample> {
+    let lhs = Expression {
+        sums: vec![6],
+        factors: vec![
+            Factor::Phase(v(4) + v(5) + v(6)),
+            Factor::Dimension(v(6), 2),
+            Factor::Wigner6j([v(0), v(1), v(6), v(2), v(3), v(4)]),
+            Factor::Wigner6j([v(0), v(1), v(6), v(3), v(2), v(5)]),
+        ],
+    };
+    let rhs = Expression {
+        sums: vec![],
+        factors: vec![
+            Factor::Wigner6j([v(0), v(2), v(5), v(1), v(3), v(4)]),
+        ],
+    };
+    check("Racah sum rule", &[Free::Tj; 6], tj_max,
+          &[[0, 3, 4], [2, 1, 4], [0, 2, 5], [3, 1, 5]], &lhs, &rhs)
+}
+
+/// Check the Biedenharn–Elliott identity
+///
+/// ```text
+/// Σ[x] (−1)^(S + x) (2 x + 1)
+///      {a b x; c d p} {c d x; e f q} {e f x; b a r}
+///   = {p q r; e a d} {p q r; f b c}
+/// ```
+///
+/// where `S = a + b + c + d + e + f + p + q + r`.  The arguments are
+/// `ta, tb, tc, td, te, tf, tp, tq, tr`.
+pub fn check_biedenharn_elliott(tj_max: i32) -> Result<(), Counterexample> {
+    let s = (0 .. 9).fold(v(9), |s, var| s + v(var));
+    let lhs = Expression {
+        sums: vec![9],
+        factors: vec![
+            Factor::Phase(s),
+            Factor::Dimension(v(9), 2),
+            Factor::Wigner6j([v(0), v(1), v(9), v(2), v(3), v(6)]),
+            Factor::Wigner6j([v(2), v(3), v(9), v(4), v(5), v(7)]),
+            Factor::Wigner6j([v(4), v(5), v(9), v(1), v(0), v(8)]),
+        ],
+    };
+    let rhs = Expression {
+        sums: vec![],
+        factors: vec![
+            Factor::Wigner6j([v(6), v(7), v(8), v(4), v(0), v(3)]),
+            Factor::Wigner6j([v(6), v(7), v(8), v(5), v(1), v(2)]),
+        ],
+    };
+    check("Biedenharn–Elliott identity", &[Free::Tj; 9], tj_max,
+          &[[0, 3, 6], [2, 1, 6], [2, 5, 7], [4, 3, 7], [4, 0, 8], [1, 5, 8]],
+          &lhs, &rhs)
+}
+
+/// Check that
+///
+/// ```text
+/// {j1 j2 j3; j4 j5 j6; j7 j8 j9}
+///   = Σ[x] (−1)^(2 x) (2 x + 1)
+///     {j1 j4 j7; j8 j9 x} {j2 j5 j8; j4 x j6} {j3 j6 j9; x j1 j2}
+/// ```
+///
+/// The arguments are `tj1, …, tj9`.
+pub fn check_9j_as_6j(tj_max: i32) -> Result<(), Counterexample> {
+    let lhs = Expression {
+        sums: vec![],
+        factors: vec![
+            Factor::Wigner9j([v(0), v(1), v(2), v(3), v(4), v(5),
+                              v(6), v(7), v(8)]),
+        ],
+    };
+    let rhs = Expression {
+        sums: vec![9],
+        factors: vec![
+            Factor::Phase(v(9) * 2),
+            Factor::Dimension(v(9), 2),
+            Factor::Wigner6j([v(0), v(3), v(6), v(7), v(8), v(9)]),
+            Factor::Wigner6j([v(1), v(4), v(7), v(3), v(9), v(5)]),
+            Factor::Wigner6j([v(2), v(5), v(8), v(9), v(0), v(1)]),
+        ],
+    };
+    check("9-j as a sum of 6-j", &[Free::Tj; 9], tj_max,
+          &[[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8]],
+          &lhs, &rhs)
+}
+
+/// States `(n1, tl1, n2, tl2)` of two oscillators with the given number of
+/// quanta and total orbital angular momentum.
+fn oscillator_pairs(energy: i32, tl: i32) -> Vec<(i32, i32, i32, i32)> {
+    let mut pairs = Vec::new();
+    for e1 in 0 .. energy + 1 {
+        for n1 in 0 .. e1 / 2 + 1 {
+            for n2 in 0 .. (energy - e1) / 2 + 1 {
+                let (tl1, tl2) = (2 * (e1 - 2 * n1), 2 * (energy - e1 - 2 * n2));
+                if triangle_condition(tl1, tl2, tl) {
+                    pairs.push((n1, tl1, n2, tl2));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Check that Talmi–Moshinsky brackets for equal masses are orthogonal,
+///
+/// ```text
+/// Σ[nr lr nc lc] ⟨nr lr, nc lc; l|n1 l1, n2 l2; l⟩
+///                ⟨nr lr, nc lc; l|n1' l1', n2' l2'; l⟩
+///   = δ(n1, n1') δ(l1, l1') δ(n2, n2') δ(l2, l2')
+/// ```
+///
+/// for every state with at most `energy_max` quanta.  The arguments are
+/// `n1, tl1, n2, tl2, n1', tl1', n2', tl2', tl`.  If the sum is not even a
+/// signed square root, `lhs` is reported as zero.  Brackets that cannot be
+/// evaluated exactly are taken as zero, which breaks the normalization of
+/// their row.
+pub fn check_moshinsky_orthogonality(
+    energy_max: i32,
+) -> Result<(), Counterexample>
+{
+    let mut memo = Brackets::equal_masses();
+    for energy in 0 .. energy_max + 1 {
+        for tl in (0 .. 2 * energy + 1).filter(|tl| tl % 2 == 0) {
+            let pairs = oscillator_pairs(energy, tl);
+            let brackets: Vec<Vec<SignedSqrt>> = pairs.iter()
+                .map(|&(n1, tl1, n2, tl2)| pairs.iter().map(|&(nr, tlr, nc, tlc)| {
+                    memo.get(MoshinskyBracket { n1, tl1, n2, tl2, nr, tlr, nc, tlc, tl })
+                        .unwrap_or_default()
+                }).collect())
+                .collect();
+            for (i, a) in brackets.iter().enumerate() {
+                for (j, b) in brackets.iter().enumerate() {
+                    let lhs = sum_radicals(a.iter().zip(b).map(|(x, y)| {
+                        x.clone() * y.clone()
+                    }));
+                    let rhs = SignedSqrt::from((i == j) as i32);
+                    if lhs.as_ref() != Some(&rhs) {
+                        let (n1, tl1, n2, tl2) = pairs[i];
+                        let (n1_, tl1_, n2_, tl2_) = pairs[j];
+                        return Err(Counterexample {
+                            identity: "Moshinsky orthogonality",
+                            args: vec![n1, tl1, n2, tl2, n1_, tl1_, n2_, tl2_, tl],
+                            lhs: lhs.unwrap_or_default(),
+                            rhs,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run all of the checks above.
+pub fn check_all(tj_max: i32) -> Result<(), Counterexample> {
+    check_3jm_orthogonality(tj_max)?;
+    check_6j_orthogonality(tj_max)?;
+    check_racah_sum_rule(tj_max)?;
+    check_biedenharn_elliott(tj_max)?;
+    check_9j_as_6j(tj_max)
+}
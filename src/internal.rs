@@ -4,6 +4,7 @@ use std::cmp::Ordering;
 use std::ops::Range;
 use rug::{Integer, Rational};
 use super::{SignedSqrt, Wigner3jm, Wigner6j, Wigner9j, Wigner12jSecond};
+use super::regge::{CanonicalRegge3jm, CanonicalRegge6j};
 
 #[inline]
 pub fn sort2<T: Ord>(a: T, b: T) -> (T, T) {
@@ -87,6 +88,42 @@ pub fn factorial(n: i32) -> Integer {
     Integer::factorial(n as u32).into()
 }
 
+/// Calculate the double factorial `n!!`.
+#[inline]
+pub fn double_factorial(n: i32) -> Integer {
+    Integer::factorial_2(n.max(0) as u32).into()
+}
+
+/// Sum terms whose radicands may differ.  Terms are grouped by radicand,
+/// which succeeds if all but one of the groups cancel.
+pub fn sum_radicals<I>(terms: I) -> Option<SignedSqrt>
+    where I: IntoIterator<Item = SignedSqrt>
+{
+    let mut groups: Vec<SignedSqrt> = Vec::new();
+    for term in terms {
+        if term.sign() == Ordering::Equal {
+            continue;
+        }
+        let mut merged = false;
+        for group in groups.iter_mut().filter(|g| g.sign() != Ordering::Equal) {
+            if let Some(sum) = group.clone().checked_add(term.clone()) {
+                *group = sum;
+                merged = true;
+                break;
+            }
+        }
+        if !merged {
+            groups.push(term);
+        }
+    }
+    let mut nonzero = groups.into_iter().filter(|g| g.sign() != Ordering::Equal);
+    let sum = nonzero.next().unwrap_or_default();
+    match nonzero.next() {
+        Some(_) => None,
+        None => Some(sum),
+    }
+}
+
 #[inline]
 pub fn phase(phi: i32) -> i32 {
     if phi % 2 == 0 {
@@ -103,20 +140,24 @@ pub fn triangle_condition(tj1: i32, tj2: i32, tj3: i32) -> bool {
     d >= 0 && d % 2 == 0 && tj3 - (tj1 - tj2).abs() >= 0
 }
 
-/// Calculate the Wigner 3-jm symbol times `(−1) ^ (j1 − j2 − m3)`.
-pub fn wigner_3jm_raw_c(this: Wigner3jm) -> SignedSqrt {
+/// Check the selection rules of the Wigner 3-jm symbol.
+#[inline]
+pub fn wigner_3jm_selection_rules(this: Wigner3jm) -> bool {
     let Wigner3jm { tj1, tm1, tj2, tm2, tj3, tm3 } = this;
     let jmr1 = (tj1 + tm1) % 2;
     let jmr2 = (tj2 + tm2) % 2;
-    if
-        tm1 + tm2 + tm3 == 0 &&
+    tm1 + tm2 + tm3 == 0 &&
         tm1.abs() <= tj1 &&
         tm2.abs() <= tj2 &&
         tm3.abs() <= tj3 &&
         jmr1 == 0 &&
         jmr2 == 0 &&
         triangle_condition(tj1, tj2, tj3)
-    {
+}
+
+/// Calculate the Wigner 3-jm symbol times `(−1) ^ (j1 − j2 − m3)`.
+pub fn wigner_3jm_raw_c(this: Wigner3jm) -> SignedSqrt {
+    if wigner_3jm_selection_rules(this) {
         wigner_3jm_raw(this)
     } else {
         Default::default()
@@ -226,7 +267,7 @@ pub fn wigner_12j_second_raw(this: Wigner12jSecond) -> SignedSqrt {
             * tetrahedral_sum(tj9, tj1, tj5, tj3, tj10, tk)
             * tetrahedral_sum(tj3, tj12, tj6, tj11, tj1, tk)
             * tetrahedral_sum(tj4, tj10, tj7, tj9, tj2, tk)
-            * tetrahedral_sum(tj11, tj2, tj7, tj4, tj12, tk)
+            * tetrahedral_sum(tj11, tj2, tj8, tj4, tj12, tk)
     }).sum();
 
     let z1 =
@@ -239,7 +280,7 @@ pub fn wigner_12j_second_raw(this: Wigner12jSecond) -> SignedSqrt {
         * triangular_factor(tj11, tj2, tj8)
         * triangular_factor(tj4, tj12, tj8);
 
-    SignedSqrt::new(Integer::from(phase(tj5 - tj6 - tj7 + tj8)) * z2, z1)
+    SignedSqrt::new(Integer::from(phase((tj5 - tj6 - tj7 + tj8) / 2)) * z2, z1)
 }
 
 
@@ -391,6 +432,49 @@ pub fn get_3tjms(
     }
 }
 
+/// Get one representative of each Regge symmetry class of the Wigner 3-jm
+/// symbol that has a member satisfying the selection rules up to a maximum
+/// of `j_max`.
+///
+/// The representatives are themselves within `j_max` and have the same value
+/// as their canonical forms.  They are visited in table order.
+pub fn get_canonical_3tjms(
+    tj_max: i32,
+    callback: &mut dyn FnMut(Wigner3jm),
+) {
+    for regge in CanonicalRegge3jm::<u32>::checked_keys(tj_max)
+        .expect("tj_max too large")
+    {
+        let w3jm = regge.smallest_representative();
+        if w3jm.tj1.max(w3jm.tj2).max(w3jm.tj3) <= tj_max {
+            callback(w3jm);
+        }
+    }
+}
+
+/// Get one representative of each Regge symmetry class of the Wigner 6-j
+/// symbol that has a member satisfying the selection rules up to a maximum
+/// of `j_max`.
+///
+/// The representatives are themselves within `j_max`.  They are visited in
+/// table order.
+pub fn get_canonical_6tjs(
+    tj_max: i32,
+    callback: &mut dyn FnMut(Wigner6j),
+) {
+    for regge in CanonicalRegge6j::<u32>::checked_keys(tj_max)
+        .expect("tj_max too large")
+    {
+        let w6j = Wigner6j::from(regge);
+        if *[
+            w6j.tj1, w6j.tj2, w6j.tj3,
+            w6j.tj4, w6j.tj5, w6j.tj6,
+        ].iter().max().unwrap() <= tj_max {
+            callback(w6j);
+        }
+    }
+}
+
 /// Get all possible arguments of the Wigner 6-j symbol that satisfy the
 /// selection rules up to a maximum of `j_max`.
 pub fn get_6tjs(
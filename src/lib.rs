@@ -1,7 +1,25 @@
 extern crate rug;
 
+pub mod cfp;
+pub mod coupling;
+pub mod dirac;
+pub mod helicity;
+pub mod hyperspherical;
+pub mod identities;
 pub mod internal;
+pub mod moshinsky;
+pub mod qdeformed;
+pub mod racah;
+pub mod recoupling;
 pub mod regge;
+pub mod semiclassical;
+pub mod slater;
+pub mod spin_network;
+pub mod su3;
+pub mod table;
+pub mod tensor;
+pub mod tesseral;
+pub mod yutsis;
 
 use std::cmp::Ordering;
 use std::ops::Mul;
@@ -51,6 +69,31 @@ impl SignedSqrt {
     pub fn signed_sq(self) -> Rational {
         self.0
     }
+
+    /// Add two expressions.  This fails if the result cannot be represented
+    /// as a signed square root of a rational number, i.e. if the ratio of
+    /// the radicands is not the square of a rational number.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        if self.sign() == Ordering::Equal {
+            return Some(other);
+        }
+        if other.sign() == Ordering::Equal {
+            return Some(self);
+        }
+        let s1 = internal::ordering_to_i32(self.sign());
+        let s2 = internal::ordering_to_i32(other.sign());
+        let r1 = self.sq();
+        let ratio = other.sq() / &r1;
+        let (numer, denom) = ratio.into_numer_denom();
+        if !numer.is_perfect_square() || !denom.is_perfect_square() {
+            return None;
+        }
+        // self + other = (s1 + s2 √ratio) √r1
+        let k = Rational::from(s1)
+            + Rational::from(s2) * Rational::from((numer.sqrt(), denom.sqrt()));
+        let sign = Rational::from(internal::ordering_to_i32(k.cmp0()));
+        Some(SignedSqrt(sign * k.square() * r1))
+    }
 }
 
 impl Mul<SignedSqrt> for SignedSqrt {
@@ -106,6 +149,165 @@ impl From<SignedSqrt> for f64 {
     }
 }
 
+/// Number that is either real (`value`) or purely imaginary (`i value`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RealOrImaginary {
+    pub value: SignedSqrt,
+    pub imaginary: bool,
+}
+
+impl RealOrImaginary {
+    /// Construct `i^k`.
+    pub fn i_pow(k: i32) -> Self {
+        let k = k.rem_euclid(4);
+        Self {
+            value: internal::phase(k / 2).into(),
+            imaginary: k % 2 != 0,
+        }
+    }
+
+    /// Real part, or zero if imaginary.
+    pub fn re(&self) -> SignedSqrt {
+        if self.imaginary { Default::default() } else { self.value.clone() }
+    }
+
+    /// Imaginary part, or zero if real.
+    pub fn im(&self) -> SignedSqrt {
+        if self.imaginary { self.value.clone() } else { Default::default() }
+    }
+
+    /// Complex conjugate.
+    pub fn conj(self) -> Self {
+        let c = if self.imaginary { -1 } else { 1 };
+        Self { value: c * self.value, imaginary: self.imaginary }
+    }
+}
+
+impl From<SignedSqrt> for RealOrImaginary {
+    #[inline]
+    fn from(value: SignedSqrt) -> Self {
+        Self { value, imaginary: false }
+    }
+}
+
+impl Mul<RealOrImaginary> for RealOrImaginary {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self::Output {
+        let c = if self.imaginary && other.imaginary { -1 } else { 1 };
+        Self {
+            value: c * self.value * other.value,
+            imaginary: self.imaginary != other.imaginary,
+        }
+    }
+}
+
+/// Phase convention of coupling coefficients.  Each convention multiplies
+/// the Condon–Shortley values by a phase that depends only on the angular
+/// momenta.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PhaseConvention {
+    /// Condon–Shortley Clebsch-Gordan coefficients and the standard 3-jm
+    /// symbol (`ClebschGordan::value` and `Wigner3jm::value`).
+    CondonShortley,
+    /// Coupling in the order `j2 ⊗ j1`, i.e. `⟨j2 m2 j1 m1|j12 m12⟩` and
+    /// `(j2 j1 j3; m2 m1 m3)` with the arguments still given in the order
+    /// `j1, j2`:
+    ///
+    /// ```text
+    /// ⟨j2 m2 j1 m1|j12 m12⟩ = (−1)^(j1 + j2 − j12) ⟨j1 m1 j2 m2|j12 m12⟩
+    /// (j2 j1 j3; m2 m1 m3) = (−1)^(j1 + j2 + j3) (j1 j2 j3; m1 m2 m3)
+    /// ```
+    Reversed,
+    /// Fano–Racah, which uses Racah's V coefficient in place of the 3-jm
+    /// symbol:
+    ///
+    /// ```text
+    /// V(j1 j2 j3; m1 m2 m3) = (−1)^(j1 − j2 − j3) (j1 j2 j3; m1 m2 m3)
+    /// ```
+    ///
+    /// The Clebsch-Gordan coefficients are those of Condon–Shortley.
+    FanoRacah,
+    /// Time-reversal-friendly convention (Huby, Biedenharn–Louck), where
+    /// every state `|j m⟩` is rephased by `i^j` so that coupled and
+    /// uncoupled states behave alike under time reversal:
+    ///
+    /// ```text
+    /// ⟨j1 m1 j2 m2|j12 m12⟩' = i^(j12 − j1 − j2) ⟨j1 m1 j2 m2|j12 m12⟩
+    /// (j1 j2 j3; m1 m2 m3)' = i^(−j1 − j2 − j3) (j1 j2 j3; m1 m2 m3)
+    /// ```
+    TimeReversal,
+}
+
+impl PhaseConvention {
+    /// Factor `c` such that a Clebsch-Gordan coefficient in this convention
+    /// is `c` times its Condon–Shortley value.
+    pub fn clebsch_gordan_factor(
+        self,
+        tj1: i32,
+        tj2: i32,
+        tj12: i32,
+    ) -> RealOrImaginary
+    {
+        match self {
+            PhaseConvention::CondonShortley | PhaseConvention::FanoRacah => {
+                RealOrImaginary::i_pow(0)
+            }
+            PhaseConvention::Reversed => {
+                RealOrImaginary::i_pow(tj1 + tj2 - tj12)
+            }
+            PhaseConvention::TimeReversal => {
+                RealOrImaginary::i_pow((tj12 - tj1 - tj2) / 2)
+            }
+        }
+    }
+
+    /// Factor `c` such that a 3-jm symbol in this convention is `c` times
+    /// its standard value.
+    pub fn wigner_3jm_factor(
+        self,
+        tj1: i32,
+        tj2: i32,
+        tj3: i32,
+    ) -> RealOrImaginary
+    {
+        match self {
+            PhaseConvention::CondonShortley => RealOrImaginary::i_pow(0),
+            PhaseConvention::Reversed => RealOrImaginary::i_pow(tj1 + tj2 + tj3),
+            PhaseConvention::FanoRacah => RealOrImaginary::i_pow(tj1 - tj2 - tj3),
+            PhaseConvention::TimeReversal => {
+                RealOrImaginary::i_pow(-(tj1 + tj2 + tj3) / 2)
+            }
+        }
+    }
+
+    /// Convert the value of a Clebsch-Gordan coefficient from this
+    /// convention to another.
+    pub fn convert_clebsch_gordan(
+        self,
+        cg: ClebschGordan,
+        value: RealOrImaginary,
+        to: PhaseConvention,
+    ) -> RealOrImaginary
+    {
+        value
+            * self.clebsch_gordan_factor(cg.tj1, cg.tj2, cg.tj12).conj()
+            * to.clebsch_gordan_factor(cg.tj1, cg.tj2, cg.tj12)
+    }
+
+    /// Convert the value of a 3-jm symbol from this convention to another.
+    pub fn convert_3jm(
+        self,
+        w3jm: Wigner3jm,
+        value: RealOrImaginary,
+        to: PhaseConvention,
+    ) -> RealOrImaginary
+    {
+        value
+            * self.wigner_3jm_factor(w3jm.tj1, w3jm.tj2, w3jm.tj3).conj()
+            * to.wigner_3jm_factor(w3jm.tj1, w3jm.tj2, w3jm.tj3)
+    }
+}
+
 /// Clebsch-Gordan coefficient
 ///
 /// ```text
@@ -133,6 +335,46 @@ impl ClebschGordan {
         SignedSqrt((self.tj12 + 1).into())
             * internal::wigner_3jm_raw_c(self.into())
     }
+
+    /// Value in the given phase convention.
+    pub fn value_in(self, convention: PhaseConvention) -> RealOrImaginary {
+        convention.clebsch_gordan_factor(self.tj1, self.tj2, self.tj12)
+            * self.value().into()
+    }
+
+    /// Factor `c` such that `self.value() == c * Wigner3jm::from(self).value()`.
+    #[inline]
+    fn factor_3jm(self) -> SignedSqrt {
+        internal::phase((self.tj1 - self.tj2 + self.tm12) / 2)
+            * SignedSqrt((self.tj12 + 1).into())
+    }
+
+    /// Find the representative of the symmetry class of this coefficient,
+    /// returning it along with the factor `c` such that `self.value() == c *
+    /// representative.value()`.
+    ///
+    /// Unlike the 3-jm symbol, the symmetries of Clebsch-Gordan coefficients
+    /// generally rescale the value by a ratio of `√(2 j12 + 1)` factors, so
+    /// `c` is not merely a phase.
+    pub fn canonical(self) -> (Self, SignedSqrt) {
+        let (canon, phase) = Wigner3jm::from(self).canonical();
+        let canon = Self::from(canon);
+        let c = self.factor_3jm() * phase;
+        let d = canon.factor_3jm().signed_sq();
+        (canon, SignedSqrt(c.signed_sq() / d))
+    }
+
+    /// Check whether two coefficients are related by a symmetry.  If so,
+    /// returns `c` such that `self.value() == c * other.value()`.
+    pub fn equivalent(self, other: Self) -> Option<SignedSqrt> {
+        let (canon1, c1) = self.canonical();
+        let (canon2, c2) = other.canonical();
+        if canon1 == canon2 {
+            Some(SignedSqrt(c1.signed_sq() / c2.signed_sq()))
+        } else {
+            None
+        }
+    }
 }
 
 /// Wigner 3-jm symbol
@@ -163,6 +405,47 @@ impl Wigner3jm {
         internal::phase((self.tj1 - self.tj2 - self.tm3) / 2)
             * internal::wigner_3jm_raw_c(self)
     }
+
+    /// Value in the given phase convention.
+    pub fn value_in(self, convention: PhaseConvention) -> RealOrImaginary {
+        convention.wigner_3jm_factor(self.tj1, self.tj2, self.tj3)
+            * self.value().into()
+    }
+
+    /// Find the representative of the symmetry class (both classical and
+    /// Regge symmetries) of this symbol, returning it along with the phase
+    /// `p` such that `self.value() == p * representative.value()`.
+    ///
+    /// The representative is the one reconstructed from the canonical Regge
+    /// square.  Symbols that violate the selection rules are returned as is.
+    /// The phase is arbitrary for symbols that vanish by symmetry.
+    pub fn canonical(self) -> (Self, i32) {
+        if !internal::wigner_3jm_selection_rules(self) {
+            return (self, 1);
+        }
+        let mut regge = regge::Regge3jm::from(self);
+        let (_, phase) = regge.checked_canonicalize::<u32>()
+            .expect("nonnegative Regge square");
+        (regge.into(), phase)
+    }
+
+    /// Check whether two symbols are related by a symmetry.  If so, returns
+    /// the phase `p` such that `self.value() == p * other.value()`.
+    pub fn equivalent(self, other: Self) -> Option<i32> {
+        let (canon1, phase1) = self.canonical();
+        let (canon2, phase2) = other.canonical();
+        if canon1 == canon2 {
+            Some(phase1 * phase2)
+        } else {
+            None
+        }
+    }
+
+    /// Semiclassical approximation for large angular momenta.  See
+    /// `semiclassical::wigner_3jm`.
+    pub fn asymptotic_f64(self) -> f64 {
+        semiclassical::wigner_3jm(self)
+    }
 }
 
 /// Wigner 6-j symbol
@@ -193,6 +476,12 @@ impl Wigner6j {
             Default::default()
         }
     }
+
+    /// Semiclassical approximation for large angular momenta.  See
+    /// `semiclassical::wigner_6j`.
+    pub fn asymptotic_f64(self) -> f64 {
+        semiclassical::wigner_6j(self)
+    }
 }
 
 /// Wigner 9-j symbol
@@ -0,0 +1,323 @@
+//! Talmi–Moshinsky brackets of the three-dimensional harmonic oscillator.
+//!
+//! Oscillator states follow the convention of Moshinsky,
+//!
+//! ```text
+//! |n l m⟩ = (−1)^n √(4π / ((2 n)!! (2 n + 2 l + 1)!!))
+//!           (a† · a†)^n 𝒴_l^m(a†) |0⟩
+//! ```
+//!
+//! where `𝒴_l^m(r) = r^l Y_l^m(r̂)` is a solid harmonic, so the radial
+//! functions are positive near the origin.
+
+use std::{error, fmt};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use rug::{Integer, Rational};
+use super::{SignedSqrt, Wigner3jm};
+use super::internal::{binomial, double_factorial, phase, sum_radicals,
+                      triangle_condition};
+use super::recoupling::{recoupling, CouplingTree};
+use super::tensor::{Composite, Convention, CoupledState, Reduced};
+
+/// Talmi–Moshinsky bracket
+///
+/// ```text
+/// ⟨nr lr, nc lc; l|n1 l1, n2 l2; l⟩
+/// ```
+///
+/// between the states of two particles and the states of their relative
+/// (`r`) and center-of-mass (`c`) motion, each coupled to a total orbital
+/// angular momentum `l`.  The coordinates are related by
+///
+/// ```text
+/// r1 = cos β R + sin β r
+/// r2 = sin β R − cos β r
+/// ```
+///
+/// after scaling each coordinate by the square root of its mass, so
+/// `tan β = √(m2 / m1)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MoshinskyBracket {
+    pub n1: i32,
+    pub tl1: i32,
+    pub n2: i32,
+    pub tl2: i32,
+    pub nr: i32,
+    pub tlr: i32,
+    pub nc: i32,
+    pub tlc: i32,
+    pub tl: i32,
+}
+
+impl MoshinskyBracket {
+    /// Value for particles of equal mass (`tan β = 1`).
+    ///
+    /// The recursion is evaluated from scratch on every call; use
+    /// `Brackets` to evaluate many brackets.
+    pub fn value(self) -> Result<SignedSqrt, InexactBracket> {
+        Brackets::equal_masses().get(self)
+    }
+
+    /// Value for an arbitrary mass ratio `tan β = √(m2 / m1)`.
+    ///
+    /// The recursion is evaluated from scratch on every call; use
+    /// `Brackets` to evaluate many brackets.
+    pub fn value_f64(self, tan_beta: f64) -> f64 {
+        Brackets::with_mass_ratio(tan_beta).get(self)
+    }
+
+    /// Total number of oscillator quanta of the two-particle state.
+    #[inline]
+    pub fn energy(self) -> i32 {
+        (4 * self.n1 + self.tl1 + 4 * self.n2 + self.tl2) / 2
+    }
+
+    fn selection_rules(self) -> bool {
+        [self.n1, self.n2, self.nr, self.nc].iter().all(|&n| n >= 0)
+            && [self.tl1, self.tl2, self.tlr, self.tlc, self.tl].iter()
+                .all(|&tl| tl >= 0 && tl % 2 == 0)
+            && 4 * self.nr + self.tlr + 4 * self.nc + self.tlc
+                == 4 * self.n1 + self.tl1 + 4 * self.n2 + self.tl2
+            && triangle_condition(self.tl1, self.tl2, self.tl)
+            && triangle_condition(self.tlr, self.tlc, self.tl)
+    }
+}
+
+/// Error returned when the recursion for a bracket involves a sum that is
+/// not a signed square root.  Contains the bracket whose sum failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InexactBracket(pub MoshinskyBracket);
+
+impl fmt::Display for InexactBracket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot evaluate bracket {:?} exactly", self.0)
+    }
+}
+
+impl error::Error for InexactBracket {}
+
+/// Amplitudes that brackets can be computed in.
+trait Amplitude: Reduced + Clone {
+    type Error;
+    fn sqrt(c: SignedSqrt) -> Self;
+    fn sum(b: MoshinskyBracket, terms: Vec<Self>) -> Result<Self, Self::Error>;
+}
+
+impl Amplitude for SignedSqrt {
+    type Error = InexactBracket;
+
+    fn sqrt(c: SignedSqrt) -> Self {
+        c
+    }
+
+    fn sum(b: MoshinskyBracket, terms: Vec<Self>) -> Result<Self, Self::Error> {
+        sum_radicals(terms).ok_or(InexactBracket(b))
+    }
+}
+
+impl Amplitude for f64 {
+    type Error = Infallible;
+
+    fn sqrt(c: SignedSqrt) -> Self {
+        f64::from(c)
+    }
+
+    fn sum(_: MoshinskyBracket, terms: Vec<Self>) -> Result<Self, Self::Error> {
+        Ok(terms.into_iter().sum())
+    }
+}
+
+fn pow<T: Amplitude>(x: &T, k: i32) -> T {
+    (0 .. k).fold(T::sqrt(1.into()), |acc, _| acc * x.clone())
+}
+
+fn sqrt(x: i32) -> SignedSqrt {
+    SignedSqrt(x.into())
+}
+
+/// `⟨n l‖b†‖n' l'⟩` for the oscillator creation operator, in the Edmonds
+/// convention.
+fn creation(n: i32, tl: i32, n1: i32, tl1: i32) -> SignedSqrt {
+    let l = tl / 2;
+    if n == n1 && tl == tl1 + 2 {
+        sqrt(l * (2 * n + 2 * l + 1))
+    } else if n == n1 + 1 && tl == tl1 - 2 {
+        sqrt(2 * (l + 1) * n)
+    } else {
+        Default::default()
+    }
+}
+
+/// Coefficient of `|n l⟩` in the product of `|0 la⟩` and `|0 lb⟩` of the
+/// same coordinate, coupled to `l`.
+fn merge(tla: i32, tlb: i32, n: i32, tl: i32) -> SignedSqrt {
+    if tla + tlb - tl != 4 * n || !triangle_condition(tla, tlb, tl) {
+        return Default::default();
+    }
+    let (la, lb, l) = (tla / 2, tlb / 2, tl / 2);
+    let cg = phase((tla - tlb) / 2) * sqrt(tl + 1) * Wigner3jm {
+        tj1: tla,
+        tm1: 0,
+        tj2: tlb,
+        tm2: 0,
+        tj3: tl,
+        tm3: 0,
+    }.value();
+    phase(n) * cg * SignedSqrt(Rational::from((
+        Integer::from((2 * la + 1) * (2 * lb + 1))
+            * double_factorial(2 * n) * double_factorial(2 * n + 2 * l + 1),
+        Integer::from(2 * l + 1)
+            * double_factorial(2 * la + 1) * double_factorial(2 * lb + 1),
+    )))
+}
+
+/// Brackets for a fixed mass ratio, memoized.  The intermediate brackets
+/// of the recursion are shared between all brackets evaluated through the
+/// same `Brackets`.
+#[derive(Clone, Debug)]
+pub struct Brackets<T>(Memo<T>);
+
+impl Brackets<SignedSqrt> {
+    /// Brackets for particles of equal mass (`tan β = 1`).
+    pub fn equal_masses() -> Self {
+        let c = SignedSqrt(Rational::from((1, 2)));
+        Brackets(Memo::new(c.clone(), c))
+    }
+
+    /// Value of a bracket.
+    pub fn get(
+        &mut self,
+        b: MoshinskyBracket,
+    ) -> Result<SignedSqrt, InexactBracket>
+    {
+        self.0.bracket(b)
+    }
+}
+
+impl Brackets<f64> {
+    /// Brackets for an arbitrary mass ratio `tan β = √(m2 / m1)`.
+    pub fn with_mass_ratio(tan_beta: f64) -> Self {
+        let beta = tan_beta.atan();
+        Brackets(Memo::new(beta.cos(), beta.sin()))
+    }
+
+    /// Value of a bracket.
+    pub fn get(&mut self, b: MoshinskyBracket) -> f64 {
+        match self.0.bracket(b) {
+            Ok(x) => x,
+            Err(e) => match e {},
+        }
+    }
+}
+
+/// Memoized recursion for a fixed transformation.
+#[derive(Clone, Debug)]
+struct Memo<T> {
+    c: T,
+    s: T,
+    cache: HashMap<MoshinskyBracket, T>,
+}
+
+impl<T: Amplitude> Memo<T> {
+    fn new(c: T, s: T) -> Self {
+        Memo { c, s, cache: HashMap::new() }
+    }
+
+    fn bracket(&mut self, b: MoshinskyBracket) -> Result<T, T::Error> {
+        if !b.selection_rules() {
+            return Ok(T::sqrt(Default::default()));
+        }
+        if let Some(x) = self.cache.get(&b) {
+            return Ok(x.clone());
+        }
+        let x = if b.n1 > 0 {
+            self.raise(b, true)?
+        } else if b.n2 > 0 {
+            self.raise(b, false)?
+        } else {
+            self.base(b)?
+        };
+        self.cache.insert(b, x.clone());
+        Ok(x)
+    }
+
+    /// Raise `n` of the first or second particle using
+    ///
+    /// ```text
+    /// A1† · A1† = c² B†² + s² b†² + 2 c s B† · b†
+    /// A2† · A2† = s² B†² + c² b†² − 2 c s B† · b†
+    /// ```
+    ///
+    /// where `B†` and `b†` create center-of-mass and relative quanta.
+    fn raise(
+        &mut self,
+        b: MoshinskyBracket,
+        first: bool,
+    ) -> Result<T, T::Error>
+    {
+        let (c, s, k, lower, n, l) = if first {
+            (self.c.clone(), self.s.clone(), 2,
+             MoshinskyBracket { n1: b.n1 - 1, ..b }, b.n1, b.tl1 / 2)
+        } else {
+            (self.s.clone(), self.c.clone(), -2,
+             MoshinskyBracket { n2: b.n2 - 1, ..b }, b.n2, b.tl2 / 2)
+        };
+        let mut terms = Vec::new();
+        let x = self.bracket(MoshinskyBracket { nc: b.nc - 1, ..lower })?;
+        terms.push((c.clone() * c.clone() * x)
+                   .scale(-1 * sqrt(2 * b.nc * (2 * b.nc + b.tlc + 1))));
+        let x = self.bracket(MoshinskyBracket { nr: b.nr - 1, ..lower })?;
+        terms.push((s.clone() * s.clone() * x)
+                   .scale(-1 * sqrt(2 * b.nr * (2 * b.nr + b.tlr + 1))));
+        for &(nr, tlr) in &[(b.nr, b.tlr - 2), (b.nr - 1, b.tlr + 2)] {
+            for &(nc, tlc) in &[(b.nc, b.tlc - 2), (b.nc - 1, b.tlc + 2)] {
+                let x = self.bracket(
+                    MoshinskyBracket { nr, tlr, nc, tlc, ..lower })?;
+                let m = Composite {
+                    convention: Convention::Edmonds,
+                    bra: CoupledState { tj1: b.tlr, tj2: b.tlc, tj: b.tl },
+                    ket: CoupledState { tj1: tlr, tj2: tlc, tj: b.tl },
+                }.scalar_product(2, creation(b.nr, b.tlr, nr, tlr),
+                                 creation(b.nc, b.tlc, nc, tlc));
+                terms.push((c.clone() * s.clone() * x).scale(k * m));
+            }
+        }
+        // |n l⟩ = −(A† · A†) |n − 1, l⟩ / √(2 n (2 n + 2 l + 1))
+        let norm = Rational::from((-1, 2 * n * (2 * n + 2 * l + 1)));
+        Ok(T::sum(b, terms)?.scale(SignedSqrt(norm)))
+    }
+
+    /// Brackets with `n1 = n2 = 0`, from the expansion of the solid
+    /// harmonics of `r1` and `r2`.
+    fn base(&mut self, b: MoshinskyBracket) -> Result<T, T::Error> {
+        let mut terms = Vec::new();
+        for tla in (0 .. b.tl1 + 1).filter(|x| x % 2 == 0) {
+            for tlc in (0 .. b.tl2 + 1).filter(|x| x % 2 == 0) {
+                // particle 1 → (la, lb), particle 2 → (lc, ld), where a and c
+                // refer to the center of mass and b and d to the relative
+                // motion
+                let (tlb, tld) = (b.tl1 - tla, b.tl2 - tlc);
+                let coef = merge(tla, tlc, b.nc, b.tlc) * merge(tlb, tld, b.nr, b.tlr);
+                if coef.sign() == Ordering::Equal {
+                    continue;
+                }
+                let leaf = CouplingTree::leaf;
+                let couple = CouplingTree::couple;
+                let coef = coef * recoupling(
+                    &couple(couple(leaf(0, tla), leaf(1, tlb), b.tl1),
+                            couple(leaf(2, tlc), leaf(3, tld), b.tl2), b.tl),
+                    &couple(couple(leaf(1, tlb), leaf(3, tld), b.tlr),
+                            couple(leaf(0, tla), leaf(2, tlc), b.tlc), b.tl),
+                ) * SignedSqrt(Rational::from(
+                    binomial(b.tl1 / 2, tla / 2) * binomial(b.tl2 / 2, tlc / 2)));
+                let coef = phase(tld / 2) * coef;
+                terms.push(T::sqrt(coef)
+                           * pow(&self.c, (tla + tld) / 2)
+                           * pow(&self.s, (tlb + tlc) / 2));
+            }
+        }
+        T::sum(b, terms)
+    }
+}
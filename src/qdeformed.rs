@@ -0,0 +1,275 @@
+//! Coupling coefficients of the quantum group `SU(2)_q`.
+//!
+//! The formulas are those of the classical symbols with every factorial
+//! `n!` replaced by the quantum factorial `[n]! = [1] [2] … [n]`, where
+//!
+//! ```text
+//! [n] = (q^(n/2) − q^(−n/2)) / (q^(1/2) − q^(−1/2))
+//! ```
+//!
+//! Everything is computed in floating point.  At roots of unity the
+//! Clebsch-Gordan coefficients and 3-jm symbols are complex, whereas the
+//! 6-j symbols remain real.
+
+use std::f64::consts::PI;
+use std::ops::{Add, Mul};
+use super::{ClebschGordan, Wigner3jm, Wigner6j};
+use super::internal::{phase, triangle_condition, wigner_3jm_selection_rules};
+
+/// Deformation parameter `q`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Deformation {
+    /// Positive real `q`.  The classical symbols are recovered at `q = 1`.
+    Real(f64),
+    /// `q = exp(2πi / r)`, for which `[n] = sin(π n / r) / sin(π / r)`.
+    /// Only the angular momenta with `2 j ≤ r − 2` are admissible.
+    RootOfUnity(i32),
+}
+
+impl Deformation {
+    /// Quantum number `[n]`.
+    pub fn number(self, n: i32) -> f64 {
+        match self {
+            Deformation::Real(q) => {
+                let h = q.ln();
+                if h == 0.0 {
+                    f64::from(n)
+                } else {
+                    (f64::from(n) * h / 2.0).sinh() / (h / 2.0).sinh()
+                }
+            }
+            Deformation::RootOfUnity(r) => {
+                (PI * f64::from(n) / f64::from(r)).sin()
+                    / (PI / f64::from(r)).sin()
+            }
+        }
+    }
+
+    /// Quantum factorial `[n]!`.
+    pub fn factorial(self, n: i32) -> f64 {
+        (1 .. n + 1).map(|k| self.number(k)).product()
+    }
+
+    /// Whether `j1`, `j2`, `j3` satisfy the triangle condition and, at a
+    /// root of unity, `j1 + j2 + j3 ≤ r − 2`.
+    pub fn admissible(self, tj1: i32, tj2: i32, tj3: i32) -> bool {
+        triangle_condition(tj1, tj2, tj3) && match self {
+            Deformation::Real(_) => true,
+            Deformation::RootOfUnity(r) => (tj1 + tj2 + tj3) / 2 <= r - 2,
+        }
+    }
+
+    /// `q^x`, taking the principal branch at roots of unity.
+    fn power(self, x: f64) -> Complex {
+        match self {
+            Deformation::Real(q) => q.powf(x).into(),
+            Deformation::RootOfUnity(r) => {
+                let theta = 2.0 * PI * x / f64::from(r);
+                Complex { re: theta.cos(), im: theta.sin() }
+            }
+        }
+    }
+
+    /// `Δ(j1, j2, j3)²` with quantum factorials.
+    fn triangular_factor(self, tj1: i32, tj2: i32, tj3: i32) -> f64 {
+        self.factorial((tj1 + tj2 - tj3) / 2)
+            * self.factorial((tj1 - tj2 + tj3) / 2)
+            * self.factorial((-tj1 + tj2 + tj3) / 2)
+            / self.factorial((tj1 + tj2 + tj3) / 2 + 1)
+    }
+}
+
+/// Complex number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    /// Modulus `|z|`.
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Complex { re, im: 0.0 }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Complex { re: self.re + other.re, im: self.im + other.im }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Complex {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Self;
+    fn mul(self, other: f64) -> Self {
+        Complex { re: self.re * other, im: self.im * other }
+    }
+}
+
+/// Clebsch-Gordan coefficient of `SU(2)_q`
+///
+/// ```text
+/// ⟨j1 j2 m1 m2|j1 j2 j12 m12⟩_q
+/// ```
+///
+/// in the convention of Kirillov and Reshetikhin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QClebschGordan {
+    pub tj1: i32,
+    pub tm1: i32,
+    pub tj2: i32,
+    pub tm2: i32,
+    pub tj12: i32,
+    pub tm12: i32,
+}
+
+impl From<ClebschGordan> for QClebschGordan {
+    fn from(this: ClebschGordan) -> Self {
+        let ClebschGordan { tj1, tm1, tj2, tm2, tj12, tm12 } = this;
+        Self { tj1, tm1, tj2, tm2, tj12, tm12 }
+    }
+}
+
+impl QClebschGordan {
+    /// Value of the coefficient, which is zero unless the triad is
+    /// admissible.  It is real for real `q`.
+    ///
+    /// The orthogonality relations hold without complex conjugation, i.e.
+    /// `Σ ⟨j1 j2 m1 m2|j m⟩_q ⟨j1 j2 m1 m2|j' m'⟩_q = δ_(j j') δ_(m m')`.
+    pub fn value(self, q: Deformation) -> Complex {
+        let QClebschGordan { tj1, tm1, tj2, tm2, tj12, tm12 } = self;
+        if !wigner_3jm_selection_rules(Wigner3jm {
+            tj1, tm1, tj2, tm2, tj3: tj12, tm3: -tm12,
+        }) || !q.admissible(tj1, tj2, tj12) {
+            return Complex::default();
+        }
+        let jjj = (tj1 + tj2 - tj12) / 2;
+        let jsum = (tj1 + tj2 + tj12) / 2 + 1;
+        // q^((j1 + j2 − j) (j1 + j2 + j + 1) / 4 + (j1 m2 − j2 m1) / 2)
+        let pre = q.power(f64::from(2 * jjj * jsum + tj1 * tm2 - tj2 * tm1) / 8.0);
+        let norm = q.triangular_factor(tj1, tj2, tj12)
+            * q.number(tj12 + 1)
+            * [tj1 + tm1, tj1 - tm1, tj2 + tm2, tj2 - tm2, tj12 + tm12, tj12 - tm12]
+                .iter().map(|&x| q.factorial(x / 2)).product::<f64>();
+        let kmin = *[0, (tj2 - tj12 - tm1) / 2, (tj1 - tj12 + tm2) / 2]
+            .iter().max().unwrap();
+        let kmax = *[jjj, (tj1 - tm1) / 2, (tj2 + tm2) / 2].iter().min().unwrap();
+        let sum = (kmin .. kmax + 1).map(|k| {
+            let denom: f64 = [
+                k,
+                jjj - k,
+                (tj1 - tm1) / 2 - k,
+                (tj2 + tm2) / 2 - k,
+                (tj12 - tj2 + tm1) / 2 + k,
+                (tj12 - tj1 - tm2) / 2 + k,
+            ].iter().map(|&x| q.factorial(x)).product();
+            q.power(-f64::from(k * jsum) / 2.0) * (f64::from(phase(k)) / denom)
+        }).fold(Complex::default(), Add::add);
+        pre * sum * norm.sqrt()
+    }
+}
+
+/// 3-jm symbol of `SU(2)_q`, related to the Clebsch-Gordan coefficient as
+/// in the classical case:
+///
+/// ```text
+/// (j1 j2 j3; m1 m2 m3)_q = (−1)^(j1 − j2 − m3) / √[2 j3 + 1]
+///                          ⟨j1 j2 m1 m2|j1 j2 j3 −m3⟩_q
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QWigner3jm {
+    pub tj1: i32,
+    pub tm1: i32,
+    pub tj2: i32,
+    pub tm2: i32,
+    pub tj3: i32,
+    pub tm3: i32,
+}
+
+impl From<Wigner3jm> for QWigner3jm {
+    fn from(this: Wigner3jm) -> Self {
+        let Wigner3jm { tj1, tm1, tj2, tm2, tj3, tm3 } = this;
+        Self { tj1, tm1, tj2, tm2, tj3, tm3 }
+    }
+}
+
+impl QWigner3jm {
+    /// Value of the symbol, which is zero unless the triad is admissible.
+    /// It is real for real `q`.
+    pub fn value(self, q: Deformation) -> Complex {
+        let QWigner3jm { tj1, tm1, tj2, tm2, tj3, tm3 } = self;
+        let cg = QClebschGordan { tj1, tm1, tj2, tm2, tj12: tj3, tm12: -tm3 };
+        cg.value(q) * (f64::from(phase((tj1 - tj2 - tm3) / 2))
+                       / q.number(tj3 + 1).sqrt())
+    }
+}
+
+/// 6-j symbol of `SU(2)_q`
+///
+/// ```text
+/// ⎧j1 j2 j3⎫
+/// ⎩j4 j5 j6⎭_q
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QWigner6j {
+    pub tj1: i32,
+    pub tj2: i32,
+    pub tj3: i32,
+    pub tj4: i32,
+    pub tj5: i32,
+    pub tj6: i32,
+}
+
+impl From<Wigner6j> for QWigner6j {
+    fn from(this: Wigner6j) -> Self {
+        let Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = this;
+        Self { tj1, tj2, tj3, tj4, tj5, tj6 }
+    }
+}
+
+impl QWigner6j {
+    /// Value of the symbol, which is zero unless all four triads are
+    /// admissible.
+    pub fn value(self, q: Deformation) -> f64 {
+        let QWigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = self;
+        let triads = [(tj1, tj2, tj3), (tj1, tj5, tj6), (tj4, tj2, tj6), (tj4, tj5, tj3)];
+        if !triads.iter().all(|&(a, b, c)| q.admissible(a, b, c)) {
+            return 0.0;
+        }
+        let norm: f64 = triads.iter()
+            .map(|&(a, b, c)| q.triangular_factor(a, b, c))
+            .product();
+        let sums: Vec<i32> = triads.iter().map(|&(a, b, c)| (a + b + c) / 2).collect();
+        let quads = [
+            (tj1 + tj2 + tj4 + tj5) / 2,
+            (tj2 + tj3 + tj5 + tj6) / 2,
+            (tj3 + tj1 + tj6 + tj4) / 2,
+        ];
+        let kmin = *sums.iter().max().unwrap();
+        let kmax = *quads.iter().min().unwrap();
+        let sum: f64 = (kmin .. kmax + 1).map(|k| {
+            let denom: f64 = sums.iter().map(|&s| q.factorial(k - s))
+                .chain(quads.iter().map(|&s| q.factorial(s - k)))
+                .product();
+            f64::from(phase(k)) * q.factorial(k + 1) / denom
+        }).sum();
+        norm.sqrt() * sum
+    }
+}
@@ -0,0 +1,353 @@
+//! Symbolic Racah-algebra expressions with free summations.
+//!
+//! An `Expression` is a sum over internal variables of a product of phases,
+//! dimension factors, and 3-jm, 6-j, and 9-j symbols.  Every argument is a
+//! `Linear` combination of variables, which stand for doubled angular
+//! momenta (`tj`) or doubled projections (`tm`).
+
+use std::{error, fmt, ops};
+use std::cmp::{self, Ordering};
+use rug::Rational;
+use super::{SignedSqrt, Wigner3jm, Wigner6j, Wigner9j};
+use super::internal::phase;
+
+/// Identifies a variable of an expression.
+pub type Var = usize;
+
+/// `constant + Σ[i] coefficient_i × variable_i`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Linear {
+    pub constant: i32,
+    pub terms: Vec<(Var, i32)>,
+}
+
+impl Linear {
+    #[inline]
+    pub fn var(var: Var) -> Self {
+        Linear { constant: 0, terms: vec![(var, 1)] }
+    }
+
+    /// Value of the combination, or `None` if a variable is unassigned.
+    pub fn eval(&self, values: &[Option<i32>]) -> Option<i32> {
+        self.terms.iter().try_fold(self.constant, |acc, &(var, c)| {
+            values.get(var).cloned().and_then(|x| x).map(|x| acc + c * x)
+        })
+    }
+
+    /// Whether the combination consists of just the given variable.
+    fn is_var(&self, var: Var) -> bool {
+        self.constant == 0 && self.terms == [(var, 1)]
+    }
+
+    fn vars<'a>(&'a self) -> impl Iterator<Item = Var> + 'a {
+        self.terms.iter().map(|&(var, _)| var)
+    }
+}
+
+impl From<i32> for Linear {
+    #[inline]
+    fn from(constant: i32) -> Self {
+        Linear { constant, terms: Vec::new() }
+    }
+}
+
+impl ops::Add for Linear {
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        self.constant += other.constant;
+        for (var, c) in other.terms {
+            match self.terms.iter().position(|&(v, _)| v == var) {
+                Some(i) => self.terms[i].1 += c,
+                None => self.terms.push((var, c)),
+            }
+        }
+        self.terms.retain(|&(_, c)| c != 0);
+        self
+    }
+}
+
+impl ops::Neg for Linear {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
+
+impl ops::Sub for Linear {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl ops::Mul<i32> for Linear {
+    type Output = Self;
+    fn mul(mut self, other: i32) -> Self {
+        self.constant *= other;
+        for term in &mut self.terms {
+            term.1 *= other;
+        }
+        self.terms.retain(|&(_, c)| c != 0);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Factor {
+    /// `(−1)^(x / 2)`, where `x` must be even
+    Phase(Linear),
+    /// `(x + 1)^(power / 2)`, i.e. a power of `√(2 j + 1)`
+    Dimension(Linear, i32),
+    /// Arguments in the order `tj1, tm1, tj2, tm2, tj3, tm3`
+    Wigner3jm([Linear; 6]),
+    Wigner6j([Linear; 6]),
+    Wigner9j([Linear; 9]),
+    /// Kronecker delta
+    Delta(Linear, Linear),
+}
+
+impl Factor {
+    /// Triples of arguments that must satisfy the triangle condition.
+    fn triads(&self) -> &'static [[usize; 3]] {
+        match *self {
+            Factor::Phase(_) | Factor::Dimension(..) | Factor::Delta(..) => &[],
+            Factor::Wigner3jm(_) => &[[0, 2, 4]],
+            Factor::Wigner6j(_) => &[[0, 1, 2], [0, 4, 5], [3, 1, 5], [3, 4, 2]],
+            Factor::Wigner9j(_) => &[
+                [0, 1, 2], [3, 4, 5], [6, 7, 8],
+                [0, 3, 6], [1, 4, 7], [2, 5, 8],
+            ],
+        }
+    }
+
+    fn args(&self) -> Vec<&Linear> {
+        match *self {
+            Factor::Delta(ref x, ref y) => vec![x, y],
+            Factor::Phase(ref x) => vec![x],
+            Factor::Dimension(ref x, _) => vec![x],
+            Factor::Wigner3jm(ref xs) => xs.iter().collect(),
+            Factor::Wigner6j(ref xs) => xs.iter().collect(),
+            Factor::Wigner9j(ref xs) => xs.iter().collect(),
+        }
+    }
+
+    /// Range of values of `var` allowed by this factor alone, given the
+    /// other variables.  Ranges are `(min, max)` in steps of two.
+    fn range(&self, var: Var, values: &[Option<i32>]) -> Option<(i32, i32)> {
+        let args = self.args();
+        let mut range = None;
+        for triad in self.triads() {
+            for k in 0 .. 3 {
+                if !args[triad[k]].is_var(var) {
+                    continue;
+                }
+                let a = args[triad[(k + 1) % 3]].eval(values);
+                let b = args[triad[(k + 2) % 3]].eval(values);
+                if let (Some(a), Some(b)) = (a, b) {
+                    range = intersect(range, ((a - b).abs(), a + b));
+                }
+            }
+        }
+        if let Factor::Delta(..) = *self {
+            for k in 0 .. 2 {
+                if args[k].is_var(var) {
+                    if let Some(x) = args[1 - k].eval(values) {
+                        range = intersect(range, (x, x));
+                    }
+                }
+            }
+        }
+        if let Factor::Wigner3jm(_) = *self {
+            for k in 0 .. 3 {
+                if !args[2 * k + 1].is_var(var) {
+                    continue;
+                }
+                if let Some(tj) = args[2 * k].eval(values) {
+                    range = intersect(range, (-tj, tj));
+                }
+            }
+        }
+        range
+    }
+
+    fn value(&self, values: &[Option<i32>]) -> Result<SignedSqrt, RacahError> {
+        let args = self.args().iter()
+            .map(|x| x.eval(values).ok_or_else(|| unbound(x, values)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(match *self {
+            Factor::Delta(..) => ((args[0] == args[1]) as i32).into(),
+            Factor::Phase(_) => {
+                if args[0] % 2 != 0 {
+                    return Err(RacahError::OddPhase);
+                }
+                phase(args[0] / 2).into()
+            }
+            Factor::Dimension(_, power) => {
+                let d = Rational::from(args[0] + 1);
+                let d = if power < 0 { d.recip() } else { d };
+                SignedSqrt((0 .. power.abs()).fold(Rational::from(1), |acc, _| {
+                    acc * &d
+                }))
+            }
+            Factor::Wigner3jm(_) => Wigner3jm {
+                tj1: args[0],
+                tm1: args[1],
+                tj2: args[2],
+                tm2: args[3],
+                tj3: args[4],
+                tm3: args[5],
+            }.value(),
+            Factor::Wigner6j(_) => Wigner6j {
+                tj1: args[0],
+                tj2: args[1],
+                tj3: args[2],
+                tj4: args[3],
+                tj5: args[4],
+                tj6: args[5],
+            }.value(),
+            Factor::Wigner9j(_) => Wigner9j {
+                tj1: args[0],
+                tj2: args[1],
+                tj3: args[2],
+                tj4: args[3],
+                tj5: args[4],
+                tj6: args[5],
+                tj7: args[6],
+                tj8: args[7],
+                tj9: args[8],
+            }.value(),
+        })
+    }
+}
+
+fn intersect(a: Option<(i32, i32)>, b: (i32, i32)) -> Option<(i32, i32)> {
+    let a = match a {
+        Some(a) => a,
+        None => return Some(b),
+    };
+    if (a.0 - b.0) % 2 != 0 {
+        // no values of the right parity
+        return Some((0, -2));
+    }
+    Some((cmp::max(a.0, b.0), cmp::min(a.1, b.1)))
+}
+
+fn unbound(x: &Linear, values: &[Option<i32>]) -> RacahError {
+    let var = x.vars()
+        .find(|&var| values.get(var).cloned().and_then(|x| x).is_none())
+        .expect("unassigned variable");
+    RacahError::Unbound(var)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RacahError {
+    /// A free variable was not given a value.
+    Unbound(Var),
+    /// The range of a summation variable could not be deduced.
+    Unbounded(Var),
+    /// A phase has a half-integer exponent.
+    OddPhase,
+    /// The terms of the sum do not share the same radical.
+    Incommensurate,
+}
+
+impl fmt::Display for RacahError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RacahError::Unbound(var) =>
+                write!(f, "variable {} has no value", var),
+            RacahError::Unbounded(var) =>
+                write!(f, "cannot deduce the range of variable {}", var),
+            RacahError::OddPhase =>
+                write!(f, "phase with half-integer exponent"),
+            RacahError::Incommensurate =>
+                write!(f, "terms do not share the same radical"),
+        }
+    }
+}
+
+impl error::Error for RacahError {}
+
+/// `Σ[sums] ∏[factors]`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Expression {
+    pub sums: Vec<Var>,
+    pub factors: Vec<Factor>,
+}
+
+impl Expression {
+    /// Evaluate for the given values of the free variables.
+    ///
+    /// The range of each summation variable is deduced from the triangle
+    /// conditions of the symbols it appears in (or `|m| ≤ j` for a
+    /// projection), so it must appear on its own as the argument of at
+    /// least one symbol whose other arguments can be determined.
+    pub fn evaluate(
+        &self,
+        values: &[(Var, i32)],
+    ) -> Result<SignedSqrt, RacahError>
+    {
+        let len = values.iter().map(|&(var, _)| var + 1)
+            .chain(self.sums.iter().map(|&var| var + 1))
+            .max()
+            .unwrap_or(0);
+        let mut slots = vec![None; len];
+        for &(var, x) in values {
+            slots[var] = Some(x);
+        }
+        for &var in &self.sums {
+            slots[var] = None;
+        }
+        self.sum(&mut slots, &self.sums)
+    }
+
+    fn sum(
+        &self,
+        values: &mut [Option<i32>],
+        remaining: &[Var],
+    ) -> Result<SignedSqrt, RacahError>
+    {
+        if remaining.is_empty() {
+            // phases come last, as their exponents need not be integers in
+            // terms that vanish anyway
+            let mut factors: Vec<&Factor> = self.factors.iter().collect();
+            factors.sort_by_key(|factor| matches!(**factor, Factor::Phase(_)));
+            let mut product = SignedSqrt::from(1);
+            for factor in factors {
+                product = product * factor.value(values)?;
+                if product.sign() == Ordering::Equal {
+                    break;
+                }
+            }
+            return Ok(product);
+        }
+        // sum over the first variable whose range can be deduced
+        let (i, (min, max)) = remaining.iter().enumerate()
+            .filter_map(|(i, &var)| {
+                self.factors.iter()
+                    .fold(None, |range, factor| {
+                        match factor.range(var, values) {
+                            Some(r) => intersect(range, r),
+                            None => range,
+                        }
+                    })
+                    .map(|range| (i, range))
+            })
+            .next()
+            .ok_or(RacahError::Unbounded(remaining[0]))?;
+        let var = remaining[i];
+        let rest: Vec<Var> = remaining.iter().cloned()
+            .filter(|&v| v != var)
+            .collect();
+        let mut total = SignedSqrt::default();
+        let mut x = min;
+        while x <= max {
+            values[var] = Some(x);
+            let term = self.sum(values, &rest)?;
+            total = total.checked_add(term).ok_or(RacahError::Incommensurate)?;
+            x += 2;
+        }
+        values[var] = None;
+        Ok(total)
+    }
+}
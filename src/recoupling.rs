@@ -0,0 +1,236 @@
+//! Recoupling coefficients between arbitrary binary coupling trees.
+
+use std::cmp::Ordering;
+use rug::Rational;
+use super::{SignedSqrt, Wigner6j, Wigner9j};
+use super::internal::{get_bitriangular_tjs, get_triangular_tjs, phase};
+
+/// Binary coupling tree of angular momenta
+///
+/// Each leaf is an angular momentum identified by a `label`, which must be
+/// unique within the tree.  Each node couples its two subtrees to `tj`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CouplingTree {
+    Leaf {
+        label: usize,
+        tj: i32,
+    },
+    Node {
+        tj: i32,
+        left: Box<CouplingTree>,
+        right: Box<CouplingTree>,
+    },
+}
+
+impl CouplingTree {
+    #[inline]
+    pub fn leaf(label: usize, tj: i32) -> Self {
+        CouplingTree::Leaf { label, tj }
+    }
+
+    /// Couple two subtrees to `tj`.
+    #[inline]
+    pub fn couple(left: Self, right: Self, tj: i32) -> Self {
+        CouplingTree::Node {
+            tj,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /// The total angular momentum of the tree.
+    #[inline]
+    pub fn tj(&self) -> i32 {
+        match *self {
+            CouplingTree::Leaf { tj, .. } => tj,
+            CouplingTree::Node { tj, .. } => tj,
+        }
+    }
+
+    /// Labels of the leaves, sorted.
+    pub fn labels(&self) -> Vec<usize> {
+        let mut labels = Vec::new();
+        self.collect_labels(&mut labels);
+        labels.sort();
+        labels
+    }
+
+    fn collect_labels(&self, labels: &mut Vec<usize>) {
+        match *self {
+            CouplingTree::Leaf { label, .. } => labels.push(label),
+            CouplingTree::Node { ref left, ref right, .. } => {
+                left.collect_labels(labels);
+                right.collect_labels(labels);
+            }
+        }
+    }
+}
+
+/// Calculate the recoupling coefficient `⟨a|b⟩` between two coupling trees
+/// of the same angular momenta.
+///
+/// The trees are related by a sequence of exchanges of subtrees (phases),
+/// changes of coupling order (6-j symbols), and exchanges of the middle
+/// angular momenta of two couplings (9-j symbols), summed over the
+/// intermediate angular momenta that they introduce.
+///
+/// Panics if the trees do not have the same leaf labels, or if a label is
+/// repeated.
+pub fn recoupling(a: &CouplingTree, b: &CouplingTree) -> SignedSqrt {
+    let labels = a.labels();
+    assert!(labels.windows(2).all(|w| w[0] != w[1]),
+            "leaf labels must be unique");
+    assert_eq!(labels, b.labels(), "trees must have the same leaves");
+    overlap(a, b)
+}
+
+/// Add two terms of a recoupling coefficient, whose radicands always agree
+/// up to a rational square.
+fn add(a: SignedSqrt, b: SignedSqrt) -> SignedSqrt {
+    a.checked_add(b).expect("incommensurate terms in recoupling coefficient")
+}
+
+fn overlap(a: &CouplingTree, b: &CouplingTree) -> SignedSqrt {
+    if a.tj() != b.tj() {
+        return Default::default();
+    }
+    match *b {
+        CouplingTree::Leaf { tj, .. } => match *a {
+            CouplingTree::Leaf { tj: tj_a, .. } if tj_a == tj => 1.into(),
+            _ => Default::default(),
+        },
+        CouplingTree::Node { ref left, ref right, .. } => {
+            let subset = left.labels();
+            split(a, &subset).into_iter().fold(
+                Default::default(),
+                |sum, (c, x, y)| {
+                    if c.sign() == Ordering::Equal {
+                        return sum;
+                    }
+                    add(sum, c * overlap(&x, left) * overlap(&y, right))
+                },
+            )
+        }
+    }
+}
+
+/// Expand the tree `(a b)J` as a linear combination of trees `(x y)J` where
+/// `x` has leaves `subset`, which must be a proper nonempty subset of the
+/// leaves.
+fn split(
+    tree: &CouplingTree,
+    subset: &[usize],
+) -> Vec<(SignedSqrt, CouplingTree, CouplingTree)>
+{
+    let (tj, a, b) = match *tree {
+        CouplingTree::Node { tj, ref left, ref right } => (tj, left, right),
+        CouplingTree::Leaf { .. } => unreachable!("cannot split a leaf"),
+    };
+    let labels_a = a.labels();
+    let labels_b = b.labels();
+    if labels_a == subset {
+        return vec![(1.into(), (**a).clone(), (**b).clone())];
+    }
+    if labels_b == subset {
+        return vec![(swap_phase(a.tj(), b.tj(), tj).into(),
+                     (**b).clone(), (**a).clone())];
+    }
+    let p: Vec<_> = labels_a.iter().cloned()
+        .filter(|l| subset.contains(l)).collect();
+    let r: Vec<_> = labels_b.iter().cloned()
+        .filter(|l| subset.contains(l)).collect();
+    if p.is_empty() || r.len() == labels_b.len() {
+        // (a b)J → (b a)J so that the left subtree is partially or entirely
+        // contained in the subset
+        let swapped = CouplingTree::couple((**b).clone(), (**a).clone(), tj);
+        let c = SignedSqrt::from(swap_phase(a.tj(), b.tj(), tj));
+        return split(&swapped, subset).into_iter()
+            .map(|(d, x, y)| (c.clone() * d, x, y))
+            .collect();
+    }
+    let mut terms = Vec::new();
+    if r.is_empty() {
+        // ((p q)e b)J → (p (q b)h)J
+        for (c, p, q) in split(a, subset) {
+            for th in coupled_tjs(q.tj(), b.tj(), p.tj(), tj) {
+                let d = rotation(p.tj(), q.tj(), a.tj(), b.tj(), tj, th);
+                let y = CouplingTree::couple(q.clone(), (**b).clone(), th);
+                terms.push((c.clone() * d, p.clone(), y));
+            }
+        }
+    } else if p.len() == labels_a.len() {
+        // (a (r u)f)J → ((a r)g u)J
+        for (c, r, u) in split(b, &r) {
+            for tg in coupled_tjs(a.tj(), r.tj(), u.tj(), tj) {
+                let d = rotation(a.tj(), r.tj(), tg, u.tj(), tj, b.tj());
+                let x = CouplingTree::couple((**a).clone(), r.clone(), tg);
+                terms.push((c.clone() * d, x, u.clone()));
+            }
+        }
+    } else {
+        // ((p q)e (r u)f)J → ((p r)g (q u)h)J
+        for (c1, p, q) in split(a, &p) {
+            for (c2, r, u) in split(b, &r) {
+                let c = c1.clone() * c2;
+                for tg in get_triangular_tjs(i32::MAX, p.tj(), r.tj()) {
+                    for th in coupled_tjs(q.tj(), u.tj(), tg, tj) {
+                        let d = SignedSqrt(
+                            Rational::from(a.tj() + 1)
+                                * Rational::from(b.tj() + 1)
+                                * Rational::from(tg + 1)
+                                * Rational::from(th + 1),
+                        ) * Wigner9j {
+                            tj1: p.tj(),
+                            tj2: q.tj(),
+                            tj3: a.tj(),
+                            tj4: r.tj(),
+                            tj5: u.tj(),
+                            tj6: b.tj(),
+                            tj7: tg,
+                            tj8: th,
+                            tj9: tj,
+                        }.value();
+                        let x = CouplingTree::couple(p.clone(), r.clone(), tg);
+                        let y = CouplingTree::couple(q.clone(), u.clone(), th);
+                        terms.push((c.clone() * d, x, y));
+                    }
+                }
+            }
+        }
+    }
+    terms
+}
+
+/// All `tj` that can be formed by coupling `tj1` and `tj2`, and that can in
+/// turn couple with `tj3` to `tj`.
+fn coupled_tjs(tj1: i32, tj2: i32, tj3: i32, tj: i32) -> Vec<i32> {
+    get_bitriangular_tjs(i32::MAX, tj1, tj2, tj3, tj).collect()
+}
+
+/// `|(j1 j2)J⟩ = swap_phase × |(j2 j1)J⟩`
+fn swap_phase(tj1: i32, tj2: i32, tj: i32) -> i32 {
+    phase((tj1 + tj2 - tj) / 2)
+}
+
+/// `⟨((j1 j2)j12 j3)J | (j1 (j2 j3)j23)J⟩`
+fn rotation(
+    tj1: i32,
+    tj2: i32,
+    tj12: i32,
+    tj3: i32,
+    tj: i32,
+    tj23: i32,
+) -> SignedSqrt
+{
+    SignedSqrt::from(phase((tj1 + tj2 + tj3 + tj) / 2))
+        * SignedSqrt((tj12 + 1).into())
+        * SignedSqrt((tj23 + 1).into())
+        * Wigner6j {
+            tj1,
+            tj2,
+            tj3: tj12,
+            tj4: tj3,
+            tj5: tj,
+            tj6: tj23,
+        }.value()
+}
@@ -1,7 +1,110 @@
-use std::ops::{Index, IndexMut};
+use std::{error, fmt};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut, Range};
 use super::{Wigner3jm, Wigner6j};
 use super::internal::{phase, sort3, sort4};
 
+/// Error returned when a canonical Regge key cannot be represented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReggeOverflow {
+    /// A component of the key does not fit into the key type.
+    Key,
+    /// The table index does not fit into `usize`.
+    Index,
+}
+
+impl fmt::Display for ReggeOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReggeOverflow::Key => {
+                write!(f, "Regge key component out of range of key type")
+            }
+            ReggeOverflow::Index => {
+                write!(f, "Regge table index out of range of usize")
+            }
+        }
+    }
+}
+
+impl error::Error for ReggeOverflow {}
+
+/// Unsigned integer type used to store the components of canonical Regge
+/// keys.
+///
+/// Wider types allow larger angular momenta at the cost of memory.
+pub trait ReggeKey: Copy + Debug + Default + Eq + Hash + Ord {
+    /// Convert from `i32`, failing if the value is out of range.
+    fn from_i32(x: i32) -> Option<Self>;
+
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_regge_key {
+    ($($t:ty),*) => {
+        $(
+            impl ReggeKey for $t {
+                #[inline]
+                fn from_i32(x: i32) -> Option<Self> {
+                    if x < 0 || x as i64 > <$t>::MAX as i64 {
+                        None
+                    } else {
+                        Some(x as _)
+                    }
+                }
+
+                #[inline]
+                fn to_usize(self) -> usize {
+                    self as _
+                }
+            }
+        )*
+    }
+}
+
+impl_regge_key!(u8, u16, u32);
+
+#[inline]
+fn checked_key<T: ReggeKey>(x: i32) -> Result<T, ReggeOverflow> {
+    T::from_i32(x).ok_or(ReggeOverflow::Key)
+}
+
+/// Calculate the `k`-simplicial number `C(n + k − 1, k)`, or `None` on
+/// overflow.
+#[inline]
+fn checked_simplicial(n: usize, k: usize) -> Option<usize> {
+    let mut r: usize = 1;
+    for i in 0 .. k {
+        r = r.checked_mul(n.checked_add(i)?)? / (i + 1);
+    }
+    Some(r)
+}
+
+/// Find the largest `n` such that `C(n + k − 1, k) ≤ index`, returning it
+/// along with the remainder.
+#[inline]
+fn inverse_simplicial(index: usize, k: usize) -> (usize, usize) {
+    let fact = (1 ..= k).product::<usize>() as f64;
+    // C(n + k − 1, k) ≥ n^k / k!, so this is an upper bound modulo rounding
+    let mut n = (fact * index as f64).powf(1.0 / k as f64) as usize + 1;
+    while checked_simplicial(n, k).is_none_or(|c| c > index) {
+        n -= 1;
+    }
+    while checked_simplicial(n + 1, k).is_some_and(|c| c <= index) {
+        n += 1;
+    }
+    (n, index - checked_simplicial(n, k).unwrap())
+}
+
+#[inline]
+fn checked_key_usize<T: ReggeKey>(x: usize) -> Result<T, ReggeOverflow> {
+    if x > i32::MAX as usize {
+        return Err(ReggeOverflow::Key);
+    }
+    checked_key(x as i32)
+}
+
 /// Regge square for Wigner 3-jm symbols, arranged in row-major order.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Regge3jm(pub [i32; 9]);
@@ -38,6 +141,58 @@ impl From<Wigner3jm> for Regge3jm {
     }
 }
 
+impl From<Regge3jm> for Wigner3jm {
+    #[inline]
+    fn from(regge: Regge3jm) -> Self {
+        let tj = |j| regge[(1, j)] + regge[(2, j)];
+        let tm = |j| regge[(2, j)] - regge[(1, j)];
+        Wigner3jm {
+            tj1: tj(0),
+            tm1: tm(0),
+            tj2: tj(1),
+            tm2: tm(1),
+            tj3: tj(2),
+            tm3: tm(2),
+        }
+    }
+}
+
+impl<T: ReggeKey> From<CanonicalRegge3jm<T>> for Regge3jm {
+    /// Reconstruct the canonical Regge square from its key.
+    #[inline]
+    fn from(CanonicalRegge3jm { l, x, t, b, s }: CanonicalRegge3jm<T>) -> Self {
+        let (l, x, t, b, s) = (
+            l.to_usize() as i32,
+            x.to_usize() as i32,
+            t.to_usize() as i32,
+            b.to_usize() as i32,
+            s.to_usize() as i32,
+        );
+        // every row and column adds up to the magic sum
+        let magic_sum = l + x + b + s - t;
+        Regge3jm([
+            s,
+            l,
+            magic_sum - s - l,
+            x,
+            b,
+            magic_sum - x - b,
+            magic_sum - s - x,
+            magic_sum - l - b,
+            t,
+        ])
+    }
+}
+
+impl<T: ReggeKey> From<CanonicalRegge3jm<T>> for Wigner3jm {
+    /// Obtain the representative 3-jm symbol, whose value is equal to the
+    /// canonical value.  Only meaningful if the key `is_canonical`.
+    #[inline]
+    fn from(canon: CanonicalRegge3jm<T>) -> Self {
+        Regge3jm::from(canon).into()
+    }
+}
+
 impl Regge3jm {
     #[inline]
     pub fn magic_sum(&self) -> i32 {
@@ -76,8 +231,21 @@ impl Regge3jm {
 
     /// Canonicalize the Regge square using the ordering specified in [Tuzun
     /// et al (1998)](https://doi.org/10.1016/S0010-4655(98)00065-4).
+    ///
+    /// Panics if the key does not fit into `u8`.  Use `checked_canonicalize`
+    /// for larger angular momenta.
     #[inline]
     pub fn canonicalize(&mut self) -> (CanonicalRegge3jm, i32) {
+        self.checked_canonicalize().expect("Regge3jm::canonicalize")
+    }
+
+    /// Same as `canonicalize` but with a key type of choice, failing if the
+    /// key does not fit into it.
+    #[inline]
+    pub fn checked_canonicalize<T: ReggeKey>(
+        &mut self,
+    ) -> Result<(CanonicalRegge3jm<T>, i32), ReggeOverflow>
+    {
         let magic_sum = self.magic_sum();
         let parity = &mut false;
 
@@ -117,72 +285,228 @@ impl Regge3jm {
             self.swap_rows(1, 2, parity);
         }
 
-        (CanonicalRegge3jm {
-            l: self[(0, 1)] as _,
-            x: self[(1, 0)] as _,
-            t: self[(2, 2)] as _,
-            b: self[(1, 1)] as _,
-            s: self[(0, 0)] as _,
+        Ok((CanonicalRegge3jm {
+            l: checked_key(self[(0, 1)])?,
+            x: checked_key(self[(1, 0)])?,
+            t: checked_key(self[(2, 2)])?,
+            b: checked_key(self[(1, 1)])?,
+            s: checked_key(self[(0, 0)])?,
         }, if *parity {
             phase(magic_sum)
         } else {
             1
-        })
+        }))
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct CanonicalRegge3jm {
-    pub l: u8,
-    pub x: u8,
-    pub t: u8,
-    pub b: u8,
-    pub s: u8,
+pub struct CanonicalRegge3jm<T = u8> {
+    pub l: T,
+    pub x: T,
+    pub t: T,
+    pub b: T,
+    pub s: T,
 }
 
-impl CanonicalRegge3jm {
+impl<T: ReggeKey> CanonicalRegge3jm<T> {
     /// Index into a table ordered according to [Rasch and Yu
     /// (2003)](https://doi.org/10.1137/S1064827503422932).
+    ///
+    /// Panics if the index overflows `usize`.
     #[inline]
     pub fn index(self) -> usize {
-        let l = self.l as usize;
-        let x = self.x as usize;
-        let t = self.t as usize;
-        let b = self.b as usize;
-        let s = self.s as usize;
-        debug_assert!(l >= x);
-        debug_assert!(x >= t);
-        debug_assert!(t >= b);
-        debug_assert!(b >= s);
-        l * (24 + l * (50 + l * (35 + l * (10 + l)))) / 120
-            + x * (6 + x * (11 + x * (6 + x))) / 24
-            + t * (2 + t * (3 + t)) / 6
-            + b * (b + 1) / 2
-            + s
+        debug_assert!(self.l >= self.x);
+        debug_assert!(self.x >= self.t);
+        debug_assert!(self.t >= self.b);
+        debug_assert!(self.b >= self.s);
+        self.checked_index().expect("Regge index overflows usize")
     }
 
+    /// Same as `index` but returns `None` if the index overflows `usize`.
     #[inline]
-    pub fn len(tj_max: i32) -> usize {
+    pub fn checked_index(self) -> Option<usize> {
+        checked_simplicial(self.l.to_usize(), 5)?
+            .checked_add(checked_simplicial(self.x.to_usize(), 4)?)?
+            .checked_add(checked_simplicial(self.t.to_usize(), 3)?)?
+            .checked_add(checked_simplicial(self.b.to_usize(), 2)?)?
+            .checked_add(self.s.to_usize())
+    }
+
+    /// Inverse of `index`, failing if the key does not fit into `T`.
+    #[inline]
+    pub fn checked_from_index(index: usize) -> Result<Self, ReggeOverflow> {
+        let (l, index) = inverse_simplicial(index, 5);
+        let (x, index) = inverse_simplicial(index, 4);
+        let (t, index) = inverse_simplicial(index, 3);
+        let (b, s) = inverse_simplicial(index, 2);
+        Ok(Self {
+            l: checked_key_usize(l)?,
+            x: checked_key_usize(x)?,
+            t: checked_key_usize(t)?,
+            b: checked_key_usize(b)?,
+            s: checked_key_usize(s)?,
+        })
+    }
+
+    /// Whether the key is the canonical form of some 3-jm symbol.  The table
+    /// has holes at the indices of keys for which this is false.
+    #[inline]
+    pub fn is_canonical(self) -> bool {
+        Regge3jm::from(self).checked_canonicalize()
+            .map(|(regge, _)| regge) == Ok(self)
+    }
+
+    /// Find the member of the symmetry class whose largest angular momentum
+    /// is as small as possible.  Its value is equal to the canonical value.
+    /// Only meaningful if the key `is_canonical`.
+    pub fn smallest_representative(self) -> Wigner3jm {
+        let mut regge = Regge3jm::from(self);
+        let magic_sum = regge.magic_sum();
+        let parity = &mut false;
+        // the first row determines the angular momenta via tj = J − R[0][j],
+        // so pick the row or column whose smallest entry is largest
+        let row_min = |regge: &Regge3jm, i| {
+            (0 .. 3).map(|j| regge[(i, j)]).min().unwrap()
+        };
+        let col_min = |regge: &Regge3jm, j| {
+            (0 .. 3).map(|i| regge[(i, j)]).min().unwrap()
+        };
+        let best_row = (0 .. 3).max_by_key(|&i| (row_min(&regge, i), 2 - i))
+            .unwrap();
+        let best_col = (0 .. 3).max_by_key(|&j| (col_min(&regge, j), 2 - j))
+            .unwrap();
+        if col_min(&regge, best_col) > row_min(&regge, best_row) {
+            regge.transpose();
+            regge.swap_rows(0, best_col, parity);
+        } else {
+            regge.swap_rows(0, best_row, parity);
+        }
+        // odd permutations contribute (−1)^J, so undo it with another one
+        if *parity && phase(magic_sum) < 0 {
+            regge.swap_cols(1, 2, parity);
+        }
+        regge.into()
+    }
+
+    /// Iterate over all canonical keys of a table for angular momenta up to
+    /// `tj_max`, in table order.
+    #[inline]
+    pub fn checked_keys(
+        tj_max: i32,
+    ) -> Result<CanonicalRegge3jmKeys<T>, ReggeOverflow>
+    {
+        Ok(CanonicalRegge3jmKeys {
+            indices: 0 .. Self::checked_len(tj_max)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Number of table entries needed for angular momenta up to `tj_max`,
+    /// failing if the key type or `usize` is too narrow.
+    #[inline]
+    pub fn checked_len(tj_max: i32) -> Result<usize, ReggeOverflow> {
         Self {
-            l: tj_max as u8 + 1,        // j_max = j_max + j_max - 0
+            // j_max = j_max + j_max - 0
+            l: checked_key(tj_max.checked_add(1).ok_or(ReggeOverflow::Key)?)?,
             .. Default::default()
-        }.index()
+        }.checked_index().ok_or(ReggeOverflow::Index)
+    }
+}
+
+impl CanonicalRegge3jm {
+    /// Panics if the table cannot be indexed with `u8` keys.
+    #[inline]
+    pub fn len(tj_max: i32) -> usize {
+        Self::checked_len(tj_max).expect("CanonicalRegge3jm::len")
+    }
+
+    /// Panics if the key does not fit into `u8`.
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        Self::checked_from_index(index).expect("CanonicalRegge3jm::from_index")
+    }
+
+    /// Panics if the table cannot be indexed with `u8` keys.
+    #[inline]
+    pub fn keys(tj_max: i32) -> CanonicalRegge3jmKeys {
+        Self::checked_keys(tj_max).expect("CanonicalRegge3jm::keys")
+    }
+}
+
+/// Iterator over the keys of a table of canonical Regge 3-jm symbols.
+#[derive(Clone, Debug)]
+pub struct CanonicalRegge3jmKeys<T = u8> {
+    indices: Range<usize>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: ReggeKey> Iterator for CanonicalRegge3jmKeys<T> {
+    type Item = CanonicalRegge3jm<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.by_ref().map(|i| {
+            CanonicalRegge3jm::checked_from_index(i).expect("key in range")
+        }).find(|regge| regge.is_canonical())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.indices.size_hint().1)
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct CanonicalRegge6j {
-    pub e: u8,
-    pub l: u8,
-    pub x: u8,
-    pub t: u8,
-    pub b: u8,
-    pub s: u8,
+pub struct CanonicalRegge6j<T = u8> {
+    pub e: T,
+    pub l: T,
+    pub x: T,
+    pub t: T,
+    pub b: T,
+    pub s: T,
+}
+
+impl<T: ReggeKey> From<CanonicalRegge6j<T>> for Wigner6j {
+    /// Obtain a representative 6-j symbol.  Among the members of the
+    /// symmetry class, this one has the smallest largest angular momentum.
+    #[inline]
+    fn from(canon: CanonicalRegge6j<T>) -> Self {
+        let e = canon.e.to_usize() as i32;
+        let l = canon.l.to_usize() as i32;
+        let x = canon.x.to_usize() as i32;
+        let t = canon.t.to_usize() as i32;
+        let b = canon.b.to_usize() as i32;
+        let s = canon.s.to_usize() as i32;
+        // solved from α1 = j1 + j2 + j4 + j5, α2 = j1 + j3 + j4 + j6,
+        // α3 = j2 + j3 + j5 + j6, β1 = j1 + j2 + j3, β2 = j1 + j5 + j6,
+        // β3 = j2 + j4 + j6, β4 = j3 + j4 + j5
+        Wigner6j {
+            tj1: l + t,
+            tj2: e + b,
+            tj3: l + e - 2 * x + b + t,
+            tj4: l - x + s + b,
+            tj5: e - x + s + t,
+            tj6: l + e - x + s,
+        }
+    }
 }
 
 impl From<Wigner6j> for CanonicalRegge6j {
+    /// Panics if the key does not fit into `u8`.  Use
+    /// `CanonicalRegge6j::checked_from` for larger angular momenta.
     #[inline]
-    fn from(Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 }: Wigner6j) -> Self {
+    fn from(w6j: Wigner6j) -> Self {
+        Self::checked_from(w6j).expect("CanonicalRegge6j::from")
+    }
+}
+
+impl<T: ReggeKey> CanonicalRegge6j<T> {
+    /// Canonicalize a 6-j symbol, failing if the key does not fit into `T`.
+    #[inline]
+    pub fn checked_from(
+        Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 }: Wigner6j,
+    ) -> Result<Self, ReggeOverflow>
+    {
         // there's a typo in Rasch and Yu (2003):
         // (3.12) should say α3 ≥ α2 ≥ α1
         let (alpha1, alpha2, alpha3) = sort3(
@@ -196,41 +520,116 @@ impl From<Wigner6j> for CanonicalRegge6j {
             (tj2 + tj4 + tj6) / 2,
             (tj3 + tj4 + tj5) / 2,
         );
-        Self {
-            s: (alpha1 - beta1) as _,
-            b: (alpha1 - beta2) as _,
-            t: (alpha1 - beta3) as _,
-            x: (alpha1 - beta4) as _,
-            l: (alpha2 - beta4) as _,
-            e: (alpha3 - beta4) as _,
-        }
+        Ok(Self {
+            s: checked_key(alpha1 - beta1)?,
+            b: checked_key(alpha1 - beta2)?,
+            t: checked_key(alpha1 - beta3)?,
+            x: checked_key(alpha1 - beta4)?,
+            l: checked_key(alpha2 - beta4)?,
+            e: checked_key(alpha3 - beta4)?,
+        })
     }
-}
 
-impl CanonicalRegge6j {
     /// Index into a table ordered according to [Rasch and Yu
     /// (2003)](https://doi.org/10.1137/S1064827503422932).
+    ///
+    /// Panics if the index overflows `usize`.
     #[inline]
     pub fn index(self) -> usize {
         debug_assert!(self.e >= self.l);
-        let e = self.e as usize;
-        e * (120 + e * (274 + e * (225 + e * (85 + e * (15 + e))))) / 720
-            + CanonicalRegge3jm {
+        self.checked_index().expect("Regge index overflows usize")
+    }
+
+    /// Same as `index` but returns `None` if the index overflows `usize`.
+    #[inline]
+    pub fn checked_index(self) -> Option<usize> {
+        debug_assert!(self.e >= self.l);
+        checked_simplicial(self.e.to_usize(), 6)?
+            .checked_add(CanonicalRegge3jm {
                 l: self.l,
                 x: self.x,
                 b: self.b,
                 t: self.t,
                 s: self.s,
-            }.index()
+            }.checked_index()?)
     }
 
+    /// Inverse of `index`, failing if the key does not fit into `T`.
     #[inline]
-    pub fn len(tj_max: i32) -> usize {
+    pub fn checked_from_index(index: usize) -> Result<Self, ReggeOverflow> {
+        let (e, index) = inverse_simplicial(index, 6);
+        let CanonicalRegge3jm { l, x, t, b, s } =
+            CanonicalRegge3jm::checked_from_index(index)?;
+        Ok(Self { e: checked_key_usize(e)?, l, x, t, b, s })
+    }
+
+    /// Iterate over all keys of a table for angular momenta up to `tj_max`,
+    /// in table order.
+    #[inline]
+    pub fn checked_keys(
+        tj_max: i32,
+    ) -> Result<CanonicalRegge6jKeys<T>, ReggeOverflow>
+    {
+        Ok(CanonicalRegge6jKeys {
+            indices: 0 .. Self::checked_len(tj_max)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Number of table entries needed for angular momenta up to `tj_max`,
+    /// failing if the key type or `usize` is too narrow.
+    #[inline]
+    pub fn checked_len(tj_max: i32) -> Result<usize, ReggeOverflow> {
         Self {
             // α − β will always cancel at least two of the j's, with two j's
             // remaining
-            e: tj_max as u8 + 1,
+            e: checked_key(tj_max.checked_add(1).ok_or(ReggeOverflow::Key)?)?,
             .. Default::default()
-        }.index()
+        }.checked_index().ok_or(ReggeOverflow::Index)
+    }
+}
+
+impl CanonicalRegge6j {
+    /// Panics if the table cannot be indexed with `u8` keys.
+    #[inline]
+    pub fn len(tj_max: i32) -> usize {
+        Self::checked_len(tj_max).expect("CanonicalRegge6j::len")
+    }
+
+    /// Panics if the key does not fit into `u8`.
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        Self::checked_from_index(index).expect("CanonicalRegge6j::from_index")
+    }
+
+    /// Panics if the table cannot be indexed with `u8` keys.
+    #[inline]
+    pub fn keys(tj_max: i32) -> CanonicalRegge6jKeys {
+        Self::checked_keys(tj_max).expect("CanonicalRegge6j::keys")
+    }
+}
+
+/// Iterator over the keys of a table of canonical Regge 6-j symbols.
+#[derive(Clone, Debug)]
+pub struct CanonicalRegge6jKeys<T = u8> {
+    indices: Range<usize>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: ReggeKey> Iterator for CanonicalRegge6jKeys<T> {
+    type Item = CanonicalRegge6j<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().map(|i| {
+            CanonicalRegge6j::checked_from_index(i).expect("key in range")
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
     }
 }
+
+impl<T: ReggeKey> ExactSizeIterator for CanonicalRegge6jKeys<T> {}
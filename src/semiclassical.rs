@@ -0,0 +1,465 @@
+//! Semiclassical approximations of the 3-jm and 6-j symbols for large
+//! angular momenta.
+//!
+//! Following Ponzano and Regge, each angular momentum `j` is represented by
+//! a vector of length `J = j + 1/2`.  The 6-j symbol corresponds to the
+//! tetrahedron with edges `J1 … J6`, and the 3-jm symbol to the triangle
+//! formed by three vectors with `z` components `m1`, `m2`, `m3`.
+//!
+//! The approximations are accurate to `O(1 / j)` relative to the amplitude
+//! of the oscillations.  `classify_3jm` and `classify_6j` determine whether
+//! a symbol lies in the classically allowed region, where it oscillates,
+//! near a turning point, or in the forbidden region.
+
+use std::f64::consts::PI;
+use super::{Wigner3jm, Wigner6j};
+use super::internal::{phase, triangle_condition, wigner_3jm_selection_rules};
+
+/// Airy function `Ai(x)`.
+fn airy_ai(x: f64) -> f64 {
+    // Ai(0) and −Ai'(0)
+    const C1: f64 = 0.355_028_053_887_817_2;
+    const C2: f64 = 0.258_819_403_792_806_8;
+    if !(-8.0 ..= 5.0).contains(&x) {
+        let zeta = 2.0 / 3.0 * x.abs().powf(1.5);
+        // asymptotic series with coefficients u_k, truncated at the
+        // smallest term
+        let mut u = vec![1.0];
+        let mut k = 1;
+        loop {
+            let kf = f64::from(k);
+            let next = u[k as usize - 1] * (6.0 * kf - 5.0) * (6.0 * kf - 3.0)
+                * (6.0 * kf - 1.0) / ((2.0 * kf - 1.0) * 216.0 * kf);
+            if next / zeta.powi(k) > u[k as usize - 1] / zeta.powi(k - 1) || k > 40 {
+                break;
+            }
+            u.push(next);
+            k += 1;
+        }
+        if x > 0.0 {
+            let s: f64 = u.iter().enumerate()
+                .map(|(k, u)| f64::from(phase(k as i32)) * u / zeta.powi(k as i32))
+                .sum();
+            (-zeta).exp() / (2.0 * PI.sqrt() * x.powf(0.25)) * s
+        } else {
+            let (mut p, mut q) = (0.0f64, 0.0f64);
+            for (k, u) in u.iter().enumerate() {
+                let t = f64::from(phase(k as i32 / 2)) * u / zeta.powi(k as i32);
+                if k % 2 == 0 { p += t } else { q += t }
+            }
+            let chi = zeta + PI / 4.0;
+            (chi.sin() * p - chi.cos() * q) / (PI.sqrt() * (-x).powf(0.25))
+        }
+    } else {
+        // Maclaurin series Ai(x) = C1 f(x) − C2 g(x)
+        let x3 = x * x * x;
+        let (mut f, mut g) = (0.0f64, 0.0f64);
+        let (mut tf, mut tg) = (1.0f64, x);
+        let mut k = 0.0;
+        while tf.abs() + tg.abs() > 1e-17 * (f.abs() + g.abs()) {
+            f += tf;
+            g += tg;
+            k += 1.0;
+            tf *= x3 / ((3.0 * k - 1.0) * (3.0 * k));
+            tg *= x3 / ((3.0 * k) * (3.0 * k + 1.0));
+        }
+        C1 * f - C2 * g
+    }
+}
+
+/// Area squared of the triangle with sides `a`, `b`, `c` (Heron's formula).
+fn triangle_area_sq(a: f64, b: f64, c: f64) -> f64 {
+    (a + b + c) * (-a + b + c) * (a - b + c) * (a + b - c) / 16.0
+}
+
+/// Phase `ψ = Σ w_i δ_i` measured from the caustic, where `δ_i` is the
+/// deviation of the angle with cosine `c_i` from the nearest of `0` and `π`
+/// (in the allowed region) or `± acosh|c_i|` (in the forbidden region).
+fn airy_phase(weights: &[f64], cosines: &[f64], allowed: bool) -> f64 {
+    weights.iter().zip(cosines).map(|(w, &c)| {
+        w * if allowed {
+            c.clamp(-1.0, 1.0).acos() - if c < 0.0 { PI } else { 0.0 }
+        } else {
+            c.abs().max(1.0).acosh() * c.signum()
+        }
+    }).sum()
+}
+
+/// Small Wigner d-matrix element `d^j_{m' m}(β)`.
+fn wigner_small_d(tj: i32, tmp: i32, tm: i32, beta: f64) -> f64 {
+    let fact = |n: i32| (1 .. n + 1).map(f64::from).product::<f64>();
+    let (c, s) = ((beta / 2.0).cos(), (beta / 2.0).sin());
+    let dm = (tmp - tm) / 2;
+    let norm = (fact((tj + tmp) / 2) * fact((tj - tmp) / 2)
+                * fact((tj + tm) / 2) * fact((tj - tm) / 2)).sqrt();
+    let sum: f64 = (0 .. tj + 1).filter_map(|k| {
+        let denom = [(tj + tm) / 2 - k, k, (tj - tmp) / 2 - k, k + dm];
+        if denom.iter().any(|&x| x < 0) {
+            return None;
+        }
+        Some(f64::from(phase(k + dm))
+             * c.powi(tj - 2 * k - dm) * s.powi(2 * k + dm)
+             / denom.iter().map(|&x| fact(x)).product::<f64>())
+    }).sum();
+    norm * sum
+}
+
+/// Euclidean tetrahedron of a 6-j symbol, with vertices `A`, `B`, `C`, `D`
+/// and edges
+///
+/// ```text
+/// J1 = AB, J2 = BC, J3 = CA, J4 = CD, J5 = AD, J6 = BD
+/// ```
+///
+/// so that the triads of the symbol are its faces and the columns are pairs
+/// of opposite edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tetrahedron {
+    pub edges: [f64; 6],
+}
+
+/// Vertices of each edge, in the order of `Tetrahedron::edges`.
+const EDGE_VERTICES: [(usize, usize); 6] =
+    [(0, 1), (1, 2), (2, 0), (2, 3), (0, 3), (1, 3)];
+
+impl From<Wigner6j> for Tetrahedron {
+    fn from(this: Wigner6j) -> Self {
+        let Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = this;
+        let mut edges = [0.0; 6];
+        for (e, &tj) in edges.iter_mut().zip([tj1, tj2, tj3, tj4, tj5, tj6].iter()) {
+            *e = f64::from(tj + 1) / 2.0;
+        }
+        Self { edges }
+    }
+}
+
+impl Tetrahedron {
+    /// Length of the edge between two vertices.
+    fn distance(&self, u: usize, v: usize) -> f64 {
+        let i = EDGE_VERTICES.iter()
+            .position(|&(a, b)| (a, b) == (u, v) || (a, b) == (v, u))
+            .expect("distinct vertices");
+        self.edges[i]
+    }
+
+    /// Square of the volume, given by the Cayley–Menger determinant.  It is
+    /// negative if the tetrahedron cannot be realized in Euclidean space.
+    pub fn volume_sq(&self) -> f64 {
+        let l: Vec<f64> = self.edges.iter().map(|e| e * e).collect();
+        let (a, b, c, d, e, f) = (l[0], l[1], l[2], l[3], l[4], l[5]);
+        (a * d * (b + c + e + f - a - d)
+         + b * e * (a + c + d + f - b - e)
+         + c * f * (a + b + d + e - c - f)
+         - a * b * c - a * e * f - b * d * f - c * d * e) / 144.0
+    }
+
+    /// Cosines of the exterior dihedral angles (the angles between the
+    /// outward normals of the two faces that meet at each edge).  Their
+    /// magnitudes exceed one if the squared volume is negative.
+    pub fn dihedral_cosines(&self) -> [f64; 6] {
+        let mut cosines = [0.0; 6];
+        for (cos, &(u, v)) in cosines.iter_mut().zip(EDGE_VERTICES.iter()) {
+            let mut rest = (0 .. 4).filter(|&w| w != u && w != v);
+            let (w0, w1) = (rest.next().unwrap(), rest.next().unwrap());
+            let a = self.distance(u, v);
+            let (b0, c0) = (self.distance(u, w0), self.distance(v, w0));
+            let (b1, c1) = (self.distance(u, w1), self.distance(v, w1));
+            let o = self.distance(w0, w1);
+            let area0 = triangle_area_sq(a, b0, c0).sqrt();
+            let area1 = triangle_area_sq(a, b1, c1).sqrt();
+            let (a2, b02, c02) = (a * a, b0 * b0, c0 * c0);
+            let (b12, c12) = (b1 * b1, c1 * c1);
+            let num = -a2 * a2 + a2 * (b02 + c02 + b12 + c12 - 2.0 * o * o)
+                + (b02 - c02) * (c12 - b12);
+            *cos = -num / (16.0 * area0 * area1);
+        }
+        cosines
+    }
+}
+
+/// Largest `|x|` at which the uniform approximation is used in the allowed
+/// region.  Further from the caustic `ψ` is no longer measured from it.
+const UNIFORM_MAX: f64 = 6.0;
+
+/// Approximation of the 6-j symbol.
+///
+/// In the classically allowed region this is the Ponzano–Regge formula
+///
+/// ```text
+/// {j1 j2 j3; j4 j5 j6} ≈ cos(Σ J_i θ_i + π/4) / √(12π V)
+/// ```
+///
+/// where `θ_i` are the exterior dihedral angles.  Near the caustic
+/// (`V → 0`) and in the forbidden region (`V² < 0`), where the formula
+/// breaks down, the uniform approximation in terms of the Airy function is
+/// used instead:
+///
+/// ```text
+/// {j1 j2 j3; j4 j5 j6} ≈ (−1)^⌈K⌉ ζ^(1/4) Ai(∓ζ) / √(12 |V|)
+/// ```
+///
+/// where `ζ = (3 |ψ| / 2)^(2/3)` with `ψ = Σ J_i (θ_i − π [cos θ_i < 0])`
+/// in the allowed region and `ψ = Σ ± J_i acosh|cos θ_i|` in the forbidden
+/// one, and `K = Σ[cos θ_i < 0] J_i`.  On the caustic the phase of the
+/// Ponzano–Regge formula is `π K`, so it matches the Airy function if
+/// `ψ < 0` and `K` is an integer, or if `ψ > 0` and `K` is a half-integer;
+/// in either case the sign is `(−1)^⌈K⌉`, which stays the same on both
+/// sides of the caustic.
+pub fn wigner_6j(this: Wigner6j) -> f64 {
+    let Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = this;
+    if !(triangle_condition(tj1, tj2, tj3) && triangle_condition(tj1, tj5, tj6)
+         && triangle_condition(tj4, tj2, tj6) && triangle_condition(tj4, tj5, tj3))
+    {
+        return 0.0;
+    }
+    let tet = Tetrahedron::from(this);
+    let v2 = tet.volume_sq();
+    let v = v2.abs().sqrt();
+    let cosines = tet.dihedral_cosines();
+    let tk: i32 = cosines.iter().zip([tj1, tj2, tj3, tj4, tj5, tj6].iter())
+        .filter(|&(&c, _)| c < 0.0)
+        .map(|(_, &tj)| tj + 1)
+        .sum();
+    let allowed = v2 > 0.0;
+    let psi = airy_phase(&tet.edges, &cosines, allowed);
+    let zeta = (1.5 * psi.abs()).powf(2.0 / 3.0);
+    if allowed && zeta > UNIFORM_MAX {
+        let s: f64 = tet.edges.iter().zip(&cosines)
+            .map(|(j, c)| j * c.clamp(-1.0, 1.0).acos())
+            .sum();
+        (s + PI / 4.0).cos() / (12.0 * PI * v).sqrt()
+    } else {
+        let x = if allowed { -zeta } else { zeta };
+        f64::from(phase((tk + 1) / 2)) * zeta.powf(0.25) * airy_ai(x)
+            / (12.0 * v).sqrt()
+    }
+}
+
+/// Triangle of a 3-jm symbol: the vectors `J1`, `J2`, `J3` placed head to
+/// tail, with `z` components `m1`, `m2`, `m3`, projected onto the `xy`
+/// plane.  The sides of the projection are `√(J_i² − m_i²)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProjectedTriangle {
+    pub lengths: [f64; 3],
+    pub projections: [f64; 3],
+}
+
+impl From<Wigner3jm> for ProjectedTriangle {
+    fn from(this: Wigner3jm) -> Self {
+        let Wigner3jm { tj1, tm1, tj2, tm2, tj3, tm3 } = this;
+        Self {
+            lengths: [
+                f64::from(tj1 + 1) / 2.0,
+                f64::from(tj2 + 1) / 2.0,
+                f64::from(tj3 + 1) / 2.0,
+            ],
+            projections: [
+                f64::from(tm1) / 2.0,
+                f64::from(tm2) / 2.0,
+                f64::from(tm3) / 2.0,
+            ],
+        }
+    }
+}
+
+impl ProjectedTriangle {
+    /// Sides of the projected triangle.
+    fn sides(&self) -> [f64; 3] {
+        let mut sides = [0.0; 3];
+        for (i, side) in sides.iter_mut().enumerate() {
+            *side = (self.lengths[i].powi(2) - self.projections[i].powi(2)).sqrt();
+        }
+        sides
+    }
+
+    /// Square of the area of the projected triangle, which is negative if
+    /// the triangle cannot be closed.
+    pub fn area_sq(&self) -> f64 {
+        let [a, b, c] = self.sides();
+        triangle_area_sq(a, b, c)
+    }
+
+    /// Cosines of the angles `θ_i` between the outward normals of the
+    /// triangle (pointing downward) and of the vertical face through `J_i`,
+    /// and of the angles `φ_k` between the outward normals of the vertical
+    /// faces that meet at the vertex `k` (joining `J_(k−1)` and `J_k`).
+    /// Their magnitudes exceed one in the forbidden region.
+    pub fn dihedral_cosines(&self) -> ([f64; 3], [f64; 3]) {
+        let l = self.lengths;
+        let m = self.projections;
+        let p = self.sides();
+        // scalar products of the vectors J_i, which sum to zero
+        let dot = |i: usize, k: usize| if i == k {
+            l[i] * l[i]
+        } else {
+            (l[3 - i - k].powi(2) - l[i].powi(2) - l[k].powi(2)) / 2.0
+        };
+        let area = triangle_area_sq(l[0], l[1], l[2]).sqrt();
+        let (mut theta, mut phi) = ([0.0; 3], [0.0; 3]);
+        for i in 0 .. 3 {
+            // (J_0 × J_1) · (J_i × z) by the Binet–Cauchy identity
+            let n = dot(0, i) * m[1] - m[0] * dot(1, i);
+            theta[i] = -n / (2.0 * area * p[i]);
+            let k = (i + 2) % 3;
+            phi[i] = (dot(k, i) - m[k] * m[i]) / (p[k] * p[i]);
+        }
+        (theta, phi)
+    }
+
+    /// Heights `z_k` of the vertices, with the first vertex at zero.
+    fn heights(&self) -> [f64; 3] {
+        let m = self.projections;
+        [0.0, m[0], m[0] + m[1]]
+    }
+
+    /// Phase `ψ = Σ J_i δθ_i − Σ z_k δφ_k` measured from the caustic, and
+    /// `2 K = 2 (Σ[cos θ_i < 0] J_i − Σ[cos φ_k < 0] z_k)`.
+    fn caustic_phase(&self) -> (f64, i32) {
+        let (theta, phi) = self.dihedral_cosines();
+        let z = self.heights();
+        let allowed = self.area_sq() > 0.0;
+        let psi = airy_phase(&self.lengths, &theta, allowed)
+            - airy_phase(&z, &phi, allowed);
+        let k: f64 = (0 .. 3).map(|i| {
+            (if theta[i] < 0.0 { self.lengths[i] } else { 0.0 })
+                - if phi[i] < 0.0 { z[i] } else { 0.0 }
+        }).sum();
+        (psi, (2.0 * k).round() as i32)
+    }
+
+    /// Phase `Φ = Σ J_i θ_i − Σ z_k φ_k + π/4` of the semiclassical
+    /// amplitude.  Requires a positive area.
+    fn phase(&self) -> f64 {
+        let (theta, phi) = self.dihedral_cosines();
+        let z = self.heights();
+        (0 .. 3).map(|i| {
+            self.lengths[i] * theta[i].clamp(-1.0, 1.0).acos()
+                - z[i] * phi[i].clamp(-1.0, 1.0).acos()
+        }).sum::<f64>() + PI / 4.0
+    }
+}
+
+/// Brussaard–Tolhoek (Ponzano–Regge) approximation of the 3-jm symbol in
+/// the classically allowed region:
+///
+/// ```text
+/// (j1 j2 j3; m1 m2 m3) ≈ (−1)^(j1 − j2 + j3 + 1) cos Φ / √(2π A)
+/// ```
+///
+/// where `A` is the area of the projected triangle and `Φ` is given by
+/// `ProjectedTriangle::phase`.  Near the boundary of the region (`A → 0`)
+/// and in the forbidden region (`A² < 0`) the uniform approximation
+///
+/// ```text
+/// (j1 j2 j3; m1 m2 m3) ≈ (−1)^(j1 − j2 + j3 + 1 + ⌈K⌉) ζ^(1/4) Ai(∓ζ)
+///                        / √(2 |A|)
+/// ```
+///
+/// is used instead, with `ζ`, `ψ` and `K` as in `wigner_6j` but including
+/// the vertical faces: `ψ = Σ J_i δθ_i − Σ z_k δφ_k` and
+/// `K = Σ[cos θ_i < 0] J_i − Σ[cos φ_k < 0] z_k`.  The approximation
+/// requires all three `j` to be large.
+pub fn wigner_3jm(this: Wigner3jm) -> f64 {
+    if !wigner_3jm_selection_rules(this) {
+        return 0.0;
+    }
+    let triangle = ProjectedTriangle::from(this);
+    let area_sq = triangle.area_sq();
+    let allowed = area_sq > 0.0;
+    let sign = f64::from(phase((this.tj1 - this.tj2 + this.tj3) / 2 + 1));
+    let (psi, tk) = triangle.caustic_phase();
+    let zeta = (1.5 * psi.abs()).powf(2.0 / 3.0);
+    if allowed && zeta > UNIFORM_MAX {
+        sign * triangle.phase().cos() / (2.0 * PI * area_sq.sqrt()).sqrt()
+    } else {
+        let x = if allowed { -zeta } else { zeta };
+        sign * f64::from(phase((tk + 1).div_euclid(2)))
+            * zeta.powf(0.25) * airy_ai(x)
+            / (2.0 * area_sq.abs().sqrt()).sqrt()
+    }
+}
+
+/// Edmonds approximation of the 3-jm symbol for `j3 ≪ j1, j2`:
+///
+/// ```text
+/// (j1 j2 j3; m1 m2 m3) ≈ (−1)^(2 j1 + j2 − m2) d^j3_{m3, j2 − j1}(β)
+///                        / √(2 j2 + 1)
+/// ```
+///
+/// where `cos β = (m1 − m2) / (j1 + j2 + 1)`.
+pub fn wigner_3jm_edmonds(this: Wigner3jm) -> f64 {
+    if !wigner_3jm_selection_rules(this) {
+        return 0.0;
+    }
+    let Wigner3jm { tj1, tm1, tj2, tm2, tj3, tm3 } = this;
+    let cos = f64::from(tm1 - tm2) / f64::from(tj1 + tj2 + 2);
+    f64::from(phase(tj1 + (tj2 - tm2) / 2))
+        * wigner_small_d(tj3, tm3, tj2 - tj1, cos.acos())
+        / f64::from(tj2 + 1).sqrt()
+}
+
+/// Classical region of a symbol, according to the argument `x` of the Airy
+/// function in the uniform approximation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Region {
+    /// `x < −2`: the symbol oscillates.
+    Allowed,
+    /// `|x| ≤ 2`: close to a caustic, where the Ponzano–Regge formula
+    /// breaks down.
+    TurningPoint,
+    /// `x > 2`: the symbol decays exponentially.
+    Forbidden,
+}
+
+/// Geometric classification of a symbol.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Classification {
+    pub region: Region,
+    /// `V²` of the tetrahedron or `A²` of the projected triangle, which is
+    /// negative in the forbidden region.
+    pub measure_sq: f64,
+    /// Argument `x = ∓(3 |ψ| / 2)^(2/3)` of the Airy function, negative in
+    /// the allowed region, where `ψ` is the phase measured from the caustic
+    /// (see `wigner_6j`).
+    pub airy_argument: f64,
+}
+
+impl Classification {
+    fn new(measure_sq: f64, psi: f64) -> Self {
+        let zeta = (1.5 * psi.abs()).powf(2.0 / 3.0);
+        let airy_argument = if measure_sq > 0.0 { -zeta } else { zeta };
+        let region = if airy_argument < -2.0 {
+            Region::Allowed
+        } else if airy_argument > 2.0 {
+            Region::Forbidden
+        } else {
+            Region::TurningPoint
+        };
+        Self { region, measure_sq, airy_argument }
+    }
+}
+
+/// Classify a 6-j symbol.  Returns `None` if it violates the triangle
+/// conditions.
+pub fn classify_6j(this: Wigner6j) -> Option<Classification> {
+    let Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = this;
+    if !(triangle_condition(tj1, tj2, tj3) && triangle_condition(tj1, tj5, tj6)
+         && triangle_condition(tj4, tj2, tj6) && triangle_condition(tj4, tj5, tj3))
+    {
+        return None;
+    }
+    let tet = Tetrahedron::from(this);
+    let v2 = tet.volume_sq();
+    let psi = airy_phase(&tet.edges, &tet.dihedral_cosines(), v2 > 0.0);
+    Some(Classification::new(v2, psi))
+}
+
+/// Classify a 3-jm symbol, with the phase `ψ` of `wigner_3jm`.  Returns
+/// `None` if it violates the selection rules.
+pub fn classify_3jm(this: Wigner3jm) -> Option<Classification> {
+    if !wigner_3jm_selection_rules(this) {
+        return None;
+    }
+    let triangle = ProjectedTriangle::from(this);
+    Some(Classification::new(triangle.area_sq(), triangle.caustic_phase().0))
+}
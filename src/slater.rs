@@ -0,0 +1,234 @@
+//! Angular coefficients of Slater integrals in atomic structure.
+//!
+//! The electrostatic energy of a term is written as
+//!
+//! ```text
+//! E = Σ[k] f_k F^k + Σ[k] g_k G^k
+//! ```
+//!
+//! where `F^k` and `G^k` are the direct and exchange Slater integrals.
+
+use rug::Rational;
+use super::{SignedSqrt, Wigner6j};
+use super::internal::{phase, sum_radicals};
+use super::tensor::{racah_tensor, Composite, Convention, CoupledState};
+
+/// Direct matrix element of `C^k(1) · C^k(2)` in `|(ja jb) J⟩`, from
+/// Edmonds (1957), equation (7.1.6):
+///
+/// ```text
+/// ⟨ja jb J|C^k(1) · C^k(2)|ja jb J⟩
+///     = (−1)^(ja + jb + J) {ja jb J; jb ja k} ⟨ja‖C^k‖ja⟩ ⟨jb‖C^k‖jb⟩
+/// ```
+///
+/// where `caa` and `cbb` are the reduced matrix elements in the Edmonds
+/// convention.
+fn direct(
+    tja: i32,
+    tjb: i32,
+    tj: i32,
+    tk: i32,
+    caa: SignedSqrt,
+    cbb: SignedSqrt,
+) -> SignedSqrt
+{
+    phase((tja + tjb + tj) / 2) * Wigner6j {
+        tj1: tja,
+        tj2: tjb,
+        tj3: tj,
+        tj4: tjb,
+        tj5: tja,
+        tj6: tk,
+    }.value() * caa * cbb
+}
+
+/// Matrix element of `C^k(1) · C^k(2) P12` in `|(ja jb) J⟩`, where `P12`
+/// exchanges the two particles.  Since `P12 |ja jb J⟩ = (−1)^(ja + jb − J)
+/// |jb ja J⟩`, Edmonds (1957), equation (7.1.6) gives
+///
+/// ```text
+/// ⟨ja jb J|C^k(1) · C^k(2) P12|ja jb J⟩
+///     = (−1)^(ja + 3 jb) {ja jb J; ja jb k} ⟨ja‖C^k‖jb⟩ ⟨jb‖C^k‖ja⟩
+/// ```
+///
+/// where `cab` and `cba` are the reduced matrix elements in the Edmonds
+/// convention.
+fn exchange(
+    tja: i32,
+    tjb: i32,
+    tj: i32,
+    tk: i32,
+    cab: SignedSqrt,
+    cba: SignedSqrt,
+) -> SignedSqrt
+{
+    phase((tja + 3 * tjb) / 2) * Wigner6j {
+        tj1: tja,
+        tj2: tjb,
+        tj3: tj,
+        tj4: tja,
+        tj5: tjb,
+        tj6: tk,
+    }.value() * cab * cba
+}
+
+/// Two electrons `l1 l2` coupled to the term `^(2S+1)L`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TwoElectronLS {
+    pub tl1: i32,
+    pub tl2: i32,
+    pub tl: i32,
+    pub ts: i32,
+    /// Whether the electrons occupy the same shell.
+    pub equivalent: bool,
+}
+
+impl TwoElectronLS {
+    /// Coefficient `f_k` of the direct integral `F^k`.
+    pub fn direct(self, tk: i32) -> SignedSqrt {
+        direct(self.tl1, self.tl2, self.tl, tk,
+               racah_tensor(self.tl1, tk, self.tl1),
+               racah_tensor(self.tl2, tk, self.tl2))
+    }
+
+    /// Coefficient `g_k` of the exchange integral `G^k`, which is zero for
+    /// equivalent electrons.
+    pub fn exchange(self, tk: i32) -> SignedSqrt {
+        if self.equivalent {
+            return Default::default();
+        }
+        // exchanging the spins contributes (−1)^(1 − S), and the exchange
+        // term enters with a minus sign
+        phase(self.ts / 2) * exchange(
+            self.tl1, self.tl2, self.tl, tk,
+            racah_tensor(self.tl1, tk, self.tl2),
+            racah_tensor(self.tl2, tk, self.tl1),
+        )
+    }
+}
+
+/// Two electrons `(l1 j1) (l2 j2)` coupled to `J`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TwoElectronJJ {
+    pub tl1: i32,
+    pub tj1: i32,
+    pub tl2: i32,
+    pub tj2: i32,
+    pub tj: i32,
+    /// Whether the electrons occupy the same subshell.
+    pub equivalent: bool,
+}
+
+/// `⟨(l 1/2) j‖C^k‖(l' 1/2) j'⟩` in the Edmonds convention.
+fn spinor_racah_tensor(
+    tl1: i32,
+    tj1: i32,
+    tk: i32,
+    tl2: i32,
+    tj2: i32,
+) -> SignedSqrt
+{
+    Composite {
+        convention: Convention::Edmonds,
+        bra: CoupledState { tj1: tl1, tj2: 1, tj: tj1 },
+        ket: CoupledState { tj1: tl2, tj2: 1, tj: tj2 },
+    }.first(tk, racah_tensor(tl1, tk, tl2))
+}
+
+impl TwoElectronJJ {
+    /// Coefficient `f_k` of the direct integral `F^k`.
+    pub fn direct(self, tk: i32) -> SignedSqrt {
+        direct(self.tj1, self.tj2, self.tj, tk,
+               spinor_racah_tensor(self.tl1, self.tj1, tk, self.tl1, self.tj1),
+               spinor_racah_tensor(self.tl2, self.tj2, tk, self.tl2, self.tj2))
+    }
+
+    /// Coefficient `g_k` of the exchange integral `G^k`, which is zero for
+    /// equivalent electrons.
+    pub fn exchange(self, tk: i32) -> SignedSqrt {
+        if self.equivalent {
+            return Default::default();
+        }
+        -1 * exchange(
+            self.tj1, self.tj2, self.tj, tk,
+            spinor_racah_tensor(self.tl1, self.tj1, tk, self.tl2, self.tj2),
+            spinor_racah_tensor(self.tl2, self.tj2, tk, self.tl1, self.tj1),
+        )
+    }
+}
+
+/// Coefficient of fractional parentage `(l^n term{|l^(n−1) parent)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Parentage {
+    /// Identifies the parent term of `l^(n−1)`.
+    pub parent: usize,
+    pub tl: i32,
+    pub ts: i32,
+    pub cfp: SignedSqrt,
+}
+
+/// Term of a single open shell `l^n` with its fractional parentage.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShellTerm {
+    pub tl: i32,
+    pub ts: i32,
+    pub parents: Vec<Parentage>,
+}
+
+/// `⟨l^n a‖U^k‖l^n b⟩` for the unit tensor `U^k = Σ[i] u^k(i)`, where
+/// `⟨l‖u^k‖l⟩ = 1`, in the Edmonds convention.
+fn unit_tensor(
+    tl: i32,
+    n: i32,
+    a: &ShellTerm,
+    b: &ShellTerm,
+    tk: i32,
+) -> Option<SignedSqrt>
+{
+    if a.ts != b.ts {
+        return Some(Default::default());
+    }
+    let mut terms = Vec::new();
+    for pa in &a.parents {
+        for pb in b.parents.iter().filter(|pb| pb.parent == pa.parent) {
+            let c = Composite {
+                convention: Convention::Edmonds,
+                bra: CoupledState { tj1: pa.tl, tj2: tl, tj: a.tl },
+                ket: CoupledState { tj1: pb.tl, tj2: tl, tj: b.tl },
+            }.second(tk, SignedSqrt::from(n));
+            terms.push(c * pa.cfp.clone() * pb.cfp.clone());
+        }
+    }
+    sum_radicals(terms)
+}
+
+/// Coefficient `f_k` of `F^k` for the term `terms[index]` of a single open
+/// shell `l^n`.
+///
+/// `terms` must contain every term of `l^n` with the same spin as the term
+/// of interest (terms with other spins are ignored), along with the
+/// coefficients of fractional parentage, which must be consistent among
+/// themselves.  Returns `None` if the calculation involves sums that cannot
+/// be represented exactly.
+pub fn shell_direct(
+    tl: i32,
+    n: i32,
+    terms: &[ShellTerm],
+    index: usize,
+    tk: i32,
+) -> Option<SignedSqrt>
+{
+    // Σ[i<j] C(i)·C(j) = ⟨l‖C^k‖l⟩² (U·U − n / (2 l + 1)) / 2
+    let term = &terms[index];
+    let mut uu = Rational::new();
+    for other in terms {
+        let u = unit_tensor(tl, n, term, other, tk)?;
+        uu += u.sq();
+    }
+    uu /= term.tl + 1;
+    uu -= Rational::from((n, tl + 1));
+    let c = racah_tensor(tl, tk, tl).sq();
+    let f: Rational = c * uu / 2;
+    let sq = f.clone().abs() * &f;
+    Some(SignedSqrt(sq))
+}
@@ -0,0 +1,123 @@
+//! Evaluation of spin networks in the Penrose/Kauffman–Lins normalization.
+//!
+//! Edges carry `2 j` strands.  A closed loop of `2 j` strands evaluates to
+//! `Δ = (−1)^(2 j) (2 j + 1)`, and the trivalent vertices are unnormalized
+//! symmetrizers, so evaluations are integers or rationals with signs that
+//! differ from the unitary (Wigner) normalization.  In terms of the latter,
+//!
+//! ```text
+//! {j1 j2 j3; j4 j5 j6} = Tet / √|θ(j1, j2, j3) θ(j1, j5, j6)
+//!                                θ(j4, j2, j6) θ(j4, j5, j3)|
+//! ```
+
+use rug::{Integer, Rational};
+use super::internal::{factorial, phase, triangle_condition};
+
+/// Value of a loop, `Δ = (−1)^(2 j) (2 j + 1)`.
+pub fn loop_value(tj: i32) -> Rational {
+    Rational::from(phase(tj) * (tj + 1))
+}
+
+/// Value of the theta network, two trivalent vertices joined by edges
+/// `j1`, `j2`, `j3`:
+///
+/// ```text
+/// θ = (−1)^(a + b + c) (a + b + c + 1)! a! b! c! / ((a + b)! (b + c)! (c + a)!)
+/// ```
+///
+/// where `a = j2 + j3 − j1`, `b = j3 + j1 − j2`, and `c = j1 + j2 − j3`.
+/// Zero unless the triangle condition holds.
+pub fn theta(tj1: i32, tj2: i32, tj3: i32) -> Rational {
+    if !triangle_condition(tj1, tj2, tj3) {
+        return Rational::new();
+    }
+    let a = (tj2 + tj3 - tj1) / 2;
+    let b = (tj3 + tj1 - tj2) / 2;
+    let c = (tj1 + tj2 - tj3) / 2;
+    Rational::from((
+        phase(a + b + c)
+            * factorial(a + b + c + 1) * factorial(a) * factorial(b) * factorial(c),
+        factorial(a + b) * factorial(b + c) * factorial(c + a),
+    ))
+}
+
+/// Tetrahedral network whose four vertices are the triads `(j1, j2, j3)`,
+/// `(j1, j5, j6)`, `(j4, j2, j6)`, and `(j4, j5, j3)`, as in the 6-j symbol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tetrahedron {
+    pub tj1: i32,
+    pub tj2: i32,
+    pub tj3: i32,
+    pub tj4: i32,
+    pub tj5: i32,
+    pub tj6: i32,
+}
+
+impl Tetrahedron {
+    /// Value of the network:
+    ///
+    /// ```text
+    /// Tet = (Π[i, j] (b_j − a_i)! / Π[e] (2 j_e)!)
+    ///       Σ[s] (−1)^s (s + 1)! / (Π[i] (s − a_i)! Π[j] (b_j − s)!)
+    /// ```
+    ///
+    /// where `a_i` are the sums of the triads and `b_j` are the sums of
+    /// opposite pairs of edges.
+    pub fn value(self) -> Rational {
+        let Tetrahedron { tj1, tj2, tj3, tj4, tj5, tj6 } = self;
+        let triads = [(tj1, tj2, tj3), (tj1, tj5, tj6), (tj4, tj2, tj6), (tj4, tj5, tj3)];
+        if !triads.iter().all(|&(a, b, c)| triangle_condition(a, b, c)) {
+            return Rational::new();
+        }
+        let a: Vec<i32> = triads.iter().map(|&(a, b, c)| (a + b + c) / 2).collect();
+        let b = [
+            (tj1 + tj2 + tj4 + tj5) / 2,
+            (tj2 + tj3 + tj5 + tj6) / 2,
+            (tj3 + tj1 + tj6 + tj4) / 2,
+        ];
+        let mut numer = Integer::from(1);
+        for ai in &a {
+            for bj in &b {
+                numer *= factorial(bj - ai);
+            }
+        }
+        let denom: Integer = [tj1, tj2, tj3, tj4, tj5, tj6].iter()
+            .map(|&tj| factorial(tj))
+            .product();
+        let smin = *a.iter().max().unwrap();
+        let smax = *b.iter().min().unwrap();
+        let sum: Rational = (smin .. smax + 1).map(|s| {
+            let d: Integer = a.iter().map(|&ai| factorial(s - ai))
+                .chain(b.iter().map(|&bj| factorial(bj - s)))
+                .product();
+            Rational::from((phase(s) * factorial(s + 1), d))
+        }).sum();
+        Rational::from((numer, denom)) * sum
+    }
+}
+
+/// 4-valent intertwiner with edges `j1`, `j2`, `j3`, `j4`, decomposed into
+/// two trivalent vertices `(j1, j2, j)` and `(j, j3, j4)` joined by an
+/// internal edge `j`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Intertwiner {
+    pub tj1: i32,
+    pub tj2: i32,
+    pub tj3: i32,
+    pub tj4: i32,
+    pub tj: i32,
+}
+
+impl Intertwiner {
+    /// Evaluation of the intertwiner contracted with its mirror image:
+    ///
+    /// ```text
+    /// ⟨ι_j|ι_j⟩ = θ(j1, j2, j) θ(j, j3, j4) / Δ_j
+    /// ```
+    ///
+    /// Intertwiners with different `j` are orthogonal.
+    pub fn norm(self) -> Rational {
+        theta(self.tj1, self.tj2, self.tj) * theta(self.tj, self.tj3, self.tj4)
+            / loop_value(self.tj)
+    }
+}
@@ -0,0 +1,769 @@
+//! Clebsch–Gordan coefficients of SU(3) in the basis SU(3) ⊃ SU(2) × U(1),
+//! and in the Elliott basis SU(3) ⊃ SO(3) (see `ElliottCoupling`).
+//!
+//! States of the irreducible representation `(λ, μ)` are labeled by the
+//! hypercharge `Y`, the isospin `I`, and its projection `I_z`, or
+//! equivalently by the Gelfand–Tsetlin pattern
+//!
+//! ```text
+//! ⎛λ + μ   μ   0⎞
+//! ⎜   m12   m22 ⎟
+//! ⎝     m11     ⎠
+//! ```
+//!
+//! where `Y = m12 + m22 − 2 (λ + 2 μ) / 3`, `I = (m12 − m22) / 2`, and `I_z =
+//! m11 − (m12 + m22) / 2`.  The phases are those of Gelfand and Tsetlin: the
+//! matrix elements of the lowering operators `E21` and `E32` are
+//! nonnegative, so the isospin multiplets follow Condon–Shortley.
+//!
+//! The representations are realized on polynomials of `x` (degree `λ`) and
+//! `y` (degree `μ`) with the Fischer inner product, on which the generators
+//! `E_ij = x_i ∂/∂x_j − y_j ∂/∂y_i` act with integer coefficients, so the
+//! calculations are exact.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use rug::Rational;
+use super::{ClebschGordan, SignedSqrt};
+use super::internal::factorial;
+
+/// Irreducible representation `(λ, μ)` of SU(3).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Irrep {
+    pub lambda: i32,
+    pub mu: i32,
+}
+
+/// Gelfand–Tsetlin pattern `(m12, m22, m11)`.
+type Pattern = (i32, i32, i32);
+
+impl Irrep {
+    #[inline]
+    pub fn dimension(self) -> usize {
+        let (lambda, mu) = (self.lambda, self.mu);
+        ((lambda + 1) * (mu + 1) * (lambda + mu + 2) / 2) as usize
+    }
+
+    /// Patterns ordered by decreasing `Y`, then `I`, then `I_z`.
+    fn patterns(self) -> Vec<Pattern> {
+        let top = self.lambda + self.mu;
+        let mut patterns = Vec::new();
+        for y in (self.mu .. top + self.mu + 1).rev() {
+            for m12 in (self.mu .. top + 1).rev() {
+                let m22 = y - m12;
+                if m22 < 0 || m22 > self.mu || m22 > m12 {
+                    continue;
+                }
+                for m11 in (m22 .. m12 + 1).rev() {
+                    patterns.push((m12, m22, m11));
+                }
+            }
+        }
+        patterns
+    }
+
+    fn state(self, (m12, m22, m11): Pattern) -> State {
+        State {
+            y3: 3 * (m12 + m22) - 2 * (self.lambda + 2 * self.mu),
+            ti: m12 - m22,
+            tm: 2 * m11 - m12 - m22,
+        }
+    }
+
+    /// States in the order of decreasing `Y`, then `I`, then `I_z`.
+    pub fn states(self) -> Vec<State> {
+        self.patterns().into_iter().map(|p| self.state(p)).collect()
+    }
+
+    /// States of the Elliott basis, ordered by `K`, then `L`.
+    pub fn elliott_states(self) -> Vec<ElliottState> {
+        let (low, high) = (self.lambda.min(self.mu), self.lambda.max(self.mu));
+        let mut states = Vec::new();
+        for k in (low % 2 .. low + 1).step_by(2) {
+            for l in k .. k + high + 1 {
+                if k == 0 && (high - l) % 2 != 0 {
+                    continue;
+                }
+                states.push(ElliottState { tk: 2 * k, tl: 2 * l });
+            }
+        }
+        states
+    }
+
+    /// Gelfand–Tsetlin pattern of the intrinsic state `χ_K`, whose `L_z` is
+    /// `K ≥ 0`.
+    fn intrinsic(self, k: i32) -> Pattern {
+        let top = self.lambda + self.mu;
+        if self.lambda >= self.mu {
+            (top, (k + self.mu) / 2, top)
+        } else {
+            ((k + self.lambda) / 2 + self.mu, 0, 0)
+        }
+    }
+
+    /// Weight of a pattern in Dynkin labels.
+    fn weight(self, (m12, m22, m11): Pattern) -> (i32, i32) {
+        let w2 = m12 + m22 - m11;
+        let w3 = self.lambda + 2 * self.mu - m12 - m22;
+        (m11 - w2, w2 - w3)
+    }
+}
+
+/// State labeled by `3 Y`, `2 I`, and `2 I_z`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct State {
+    pub y3: i32,
+    pub ti: i32,
+    pub tm: i32,
+}
+
+/// State `|K L⟩` of the Elliott basis SU(3) ⊃ SO(3), labeled by `2 K` and
+/// `2 L`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ElliottState {
+    pub tk: i32,
+    pub tl: i32,
+}
+
+/// Multiplicity of `c` in `a ⊗ b`, from the Racah–Speiser algorithm.
+pub fn outer_multiplicity(a: Irrep, b: Irrep, c: Irrep) -> usize {
+    let mut count = 0;
+    for p in a.patterns() {
+        let (wp, wq) = a.weight(p);
+        let (mut p, mut q) = (wp + b.lambda + 1, wq + b.mu + 1);
+        let mut sign = 1;
+        while p < 0 || q < 0 {
+            if p < 0 {
+                q += p;
+                p = -p;
+            } else {
+                p += q;
+                q = -q;
+            }
+            sign = -sign;
+        }
+        if (p - 1, q - 1) == (c.lambda, c.mu) {
+            count += sign;
+        }
+    }
+    count as usize
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Op {
+    E12,
+    E21,
+    E23,
+    E32,
+    E13,
+    E31,
+}
+
+type Vector<K> = BTreeMap<K, Rational>;
+
+/// Sparse matrix of a generator, keyed by the generator and the column.
+type Matrix = HashMap<(Op, usize), Vec<(usize, Rational)>>;
+
+fn axpy<K: Ord + Clone>(y: &mut Vector<K>, a: &Rational, x: &Vector<K>) {
+    for (k, xk) in x {
+        let sum = {
+            let yk = y.entry(k.clone()).or_default();
+            *yk += Rational::from(a * xk);
+            yk.cmp0() == Ordering::Equal
+        };
+        if sum {
+            y.remove(k);
+        }
+    }
+}
+
+/// Space on which the generators act, with an orthogonal basis.
+trait Space {
+    type Key: Ord + Clone;
+
+    /// `⟨k|k⟩` for a basis vector.
+    fn norm(&self, key: &Self::Key) -> Rational;
+
+    fn act(&self, op: Op, key: &Self::Key) -> Vec<(Self::Key, Rational)>;
+
+    fn apply(&self, op: Op, v: &Vector<Self::Key>) -> Vector<Self::Key> {
+        let mut w = Vector::new();
+        for (k, vk) in v {
+            let image = self.act(op, k).into_iter().collect();
+            axpy(&mut w, vk, &image);
+        }
+        w
+    }
+
+    fn dot(&self, u: &Vector<Self::Key>, v: &Vector<Self::Key>) -> Rational {
+        let mut sum = Rational::new();
+        for (k, uk) in u {
+            if let Some(vk) = v.get(k) {
+                sum += Rational::from(uk * vk) * self.norm(k);
+            }
+        }
+        sum
+    }
+}
+
+/// Polynomials in `x1, x2, x3, y1, y2, y3`, keyed by their exponents.
+struct Polynomials;
+
+impl Space for Polynomials {
+    type Key = [i32; 6];
+
+    fn norm(&self, key: &[i32; 6]) -> Rational {
+        key.iter().map(|&e| factorial(e)).product::<rug::Integer>().into()
+    }
+
+    fn act(&self, op: Op, key: &[i32; 6]) -> Vec<([i32; 6], Rational)> {
+        let (i, j) = match op {
+            Op::E12 => (0, 1),
+            Op::E21 => (1, 0),
+            Op::E23 => (1, 2),
+            Op::E32 => (2, 1),
+            Op::E13 => (0, 2),
+            Op::E31 => (2, 0),
+        };
+        let mut terms = Vec::new();
+        if key[j] > 0 {
+            let mut k = *key;
+            k[j] -= 1;
+            k[i] += 1;
+            terms.push((k, Rational::from(key[j])));
+        }
+        if key[3 + i] > 0 {
+            let mut k = *key;
+            k[3 + i] -= 1;
+            k[3 + j] += 1;
+            terms.push((k, Rational::from(-key[3 + i])));
+        }
+        terms
+    }
+}
+
+/// Unnormalized Gelfand–Tsetlin states `v` with their norms `⟨v|v⟩`.
+struct GtStates<K> {
+    patterns: Vec<Pattern>,
+    vectors: Vec<Vector<K>>,
+    norms: Vec<Rational>,
+}
+
+/// Construct the Gelfand–Tsetlin states of the representation generated by
+/// the highest weight vector `hw`.
+fn gelfand_tsetlin<S: Space>(
+    space: &S,
+    irrep: Irrep,
+    hw: Vector<S::Key>,
+) -> GtStates<S::Key>
+{
+    let patterns = irrep.patterns();
+    let mut index = HashMap::new();
+    let mut vectors: Vec<Vector<S::Key>> = Vec::new();
+    let mut norms: Vec<Rational> = Vec::new();
+    for (n, &(m12, m22, m11)) in patterns.iter().enumerate() {
+        let v = if n == 0 {
+            hw.clone()
+        } else if m11 < m12 {
+            space.apply(Op::E21, &vectors[index[&(m12, m22, m11 + 1)]])
+        } else {
+            // lower from a state of higher Y and remove the components of
+            // higher isospin, which makes ⟨v|E32|parent⟩ positive
+            let mut found = None;
+            for parent in &[(m12 + 1, m22, m12), (m12, m22 + 1, m12)] {
+                let i = match index.get(parent) {
+                    Some(&i) => i,
+                    None => continue,
+                };
+                let mut w = space.apply(Op::E32, &vectors[i]);
+                for (j, &(n12, n22, n11)) in patterns[.. n].iter().enumerate() {
+                    if n12 + n22 == m12 + m22 && n11 == m11 {
+                        let c = -space.dot(&vectors[j], &w) / &norms[j];
+                        axpy(&mut w, &c, &vectors[j]);
+                    }
+                }
+                if !w.is_empty() {
+                    found = Some(w);
+                    break;
+                }
+            }
+            found.expect("state reachable from the highest weight")
+        };
+        norms.push(space.dot(&v, &v));
+        index.insert((m12, m22, m11), vectors.len());
+        vectors.push(v);
+    }
+    GtStates { patterns, vectors, norms }
+}
+
+/// Representation in the basis of its unnormalized Gelfand–Tsetlin states,
+/// keyed by their index.
+struct Irreducible {
+    patterns: Vec<Pattern>,
+    norms: Vec<Rational>,
+    /// Action of each generator on the states.
+    matrix: Matrix,
+}
+
+impl Irreducible {
+    fn new(g: &GtStates<[i32; 6]>) -> Self {
+        let mut matrix = HashMap::new();
+        for &op in &[Op::E12, Op::E21, Op::E23, Op::E32, Op::E13, Op::E31] {
+            for (s, v) in g.vectors.iter().enumerate() {
+                let w = Polynomials.apply(op, v);
+                let column = g.vectors.iter().zip(&g.norms).enumerate()
+                    .map(|(t, (u, n))| (t, Polynomials.dot(u, &w) / n))
+                    .filter(|(_, c)| c.cmp0() != Ordering::Equal)
+                    .collect();
+                matrix.insert((op, s), column);
+            }
+        }
+        Irreducible {
+            patterns: g.patterns.clone(),
+            norms: g.norms.clone(),
+            matrix,
+        }
+    }
+
+    /// The Gelfand–Tsetlin states as vectors of this space.
+    fn gt_states(&self) -> GtStates<usize> {
+        GtStates {
+            patterns: self.patterns.clone(),
+            vectors: (0 .. self.patterns.len())
+                .map(|s| Some((s, Rational::from(1))).into_iter().collect())
+                .collect(),
+            norms: self.norms.clone(),
+        }
+    }
+}
+
+impl Space for Irreducible {
+    type Key = usize;
+
+    fn norm(&self, &s: &usize) -> Rational {
+        self.norms[s].clone()
+    }
+
+    fn act(&self, op: Op, &s: &usize) -> Vec<(usize, Rational)> {
+        self.matrix[&(op, s)].clone()
+    }
+}
+
+/// Tensor product of two representations in the basis of products of their
+/// unnormalized Gelfand–Tsetlin states.
+struct Product {
+    factors: [Irreducible; 2],
+}
+
+impl Space for Product {
+    type Key = (usize, usize);
+
+    fn norm(&self, &(s1, s2): &(usize, usize)) -> Rational {
+        Rational::from(&self.factors[0].norms[s1] * &self.factors[1].norms[s2])
+    }
+
+    fn act(
+        &self,
+        op: Op,
+        &(s1, s2): &(usize, usize),
+    ) -> Vec<((usize, usize), Rational)>
+    {
+        let first = self.factors[0].matrix[&(op, s1)].iter()
+            .map(|&(t, ref c)| ((t, s2), c.clone()));
+        let second = self.factors[1].matrix[&(op, s2)].iter()
+            .map(|&(t, ref c)| ((s1, t), c.clone()));
+        first.chain(second).collect()
+    }
+}
+
+impl Product {
+    /// Product of `a` and `b`, realized on polynomials.
+    fn new(a: Irrep, b: Irrep) -> Self {
+        let irreducible = |irrep: Irrep| {
+            let mut v = Vector::new();
+            v.insert([irrep.lambda, 0, 0, 0, 0, irrep.mu], Rational::from(1));
+            Irreducible::new(&gelfand_tsetlin(&Polynomials, irrep, v))
+        };
+        Product { factors: [irreducible(a), irreducible(b)] }
+    }
+
+    /// Orthogonal highest weight states of the copies of `c`, which are
+    /// annihilated by `E12` and `E23`.  The first nonzero coefficient of
+    /// each is positive.
+    fn highest_weights(
+        &self,
+        a: Irrep,
+        b: Irrep,
+        c: Irrep,
+    ) -> Vec<Vector<(usize, usize)>>
+    {
+        let mut candidates = Vec::new();
+        for (s1, &p1) in self.factors[0].patterns.iter().enumerate() {
+            for (s2, &p2) in self.factors[1].patterns.iter().enumerate() {
+                let (w1, w2) = (a.weight(p1), b.weight(p2));
+                if (w1.0 + w2.0, w1.1 + w2.1) == (c.lambda, c.mu) {
+                    candidates.push((s1, s2));
+                }
+            }
+        }
+        let mut equations = BTreeMap::new();
+        for (i, key) in candidates.iter().enumerate() {
+            for &op in &[Op::E12, Op::E23] {
+                for (image, coef) in self.act(op, key) {
+                    equations.entry((op, image))
+                        .or_insert_with(|| vec![Rational::new(); candidates.len()])
+                        [i] += coef;
+                }
+            }
+        }
+        let kernel = null_space(equations.into_values().collect(),
+                                candidates.len());
+        let mut hws: Vec<Vector<(usize, usize)>> = Vec::new();
+        for x in kernel {
+            let mut v: Vector<(usize, usize)> = candidates.iter().cloned().zip(x)
+                .filter(|(_, c)| *c != 0)
+                .collect();
+            for u in &hws {
+                let c = -self.dot(u, &v) / self.dot(u, u);
+                axpy(&mut v, &c, u);
+            }
+            if v.values().next().map(|c| *c < 0) == Some(true) {
+                for c in v.values_mut() {
+                    *c = -c.clone();
+                }
+            }
+            hws.push(v);
+        }
+        hws
+    }
+}
+
+/// Basis of the null space of a matrix with `n` columns.
+fn null_space(mut rows: Vec<Vec<Rational>>, n: usize) -> Vec<Vec<Rational>> {
+    let mut pivots = Vec::new();
+    for col in 0 .. n {
+        let r = pivots.len();
+        let p = match (r .. rows.len()).find(|&i| rows[i][col] != 0) {
+            Some(p) => p,
+            None => continue,
+        };
+        rows.swap(r, p);
+        let pivot = rows[r][col].clone();
+        for x in &mut rows[r] {
+            *x /= &pivot;
+        }
+        let prow = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i == r || row[col] == 0 {
+                continue;
+            }
+            let f = row[col].clone();
+            for (x, y) in row.iter_mut().zip(&prow) {
+                *x -= Rational::from(&f * y);
+            }
+        }
+        pivots.push(col);
+    }
+    (0 .. n).filter(|col| !pivots.contains(col)).map(|free| {
+        let mut x = vec![Rational::new(); n];
+        x[free] = Rational::from(1);
+        for (r, &col) in pivots.iter().enumerate() {
+            x[col] = -rows[r][free].clone();
+        }
+        x
+    }).collect()
+}
+
+/// Clebsch–Gordan coefficients for the coupling `a ⊗ b → c`.
+///
+/// When `c` occurs more than once, the copies are labeled by `ρ = 0, 1, …`
+/// in the order in which their highest weight states are found, after
+/// Gram–Schmidt orthogonalization.  In every copy, the first nonzero
+/// coefficient of the highest weight state (in the order of the states of
+/// `a`, then `b`) is positive.
+#[derive(Clone, Debug)]
+pub struct Coupling {
+    pub a: Irrep,
+    pub b: Irrep,
+    pub c: Irrep,
+    multiplicity: usize,
+    coefficients: HashMap<(State, State, usize, State), SignedSqrt>,
+}
+
+impl Coupling {
+    pub fn new(a: Irrep, b: Irrep, c: Irrep) -> Self {
+        let product = Product::new(a, b);
+        let hws = product.highest_weights(a, b, c);
+        let [ref pa, ref pb] = product.factors;
+        let mut coefficients = HashMap::new();
+        for (rho, v) in hws.into_iter().enumerate() {
+            let gc = gelfand_tsetlin(&product, c, v);
+            for ((&p, v), n) in gc.patterns.iter().zip(&gc.vectors).zip(&gc.norms) {
+                for (&(s1, s2), x) in v {
+                    let sq = Rational::from(x * x) * product.norm(&(s1, s2)) / n;
+                    let value = SignedSqrt(if *x < 0 { -sq } else { sq });
+                    coefficients.insert(
+                        (a.state(pa.patterns[s1]), b.state(pb.patterns[s2]), rho,
+                         c.state(p)),
+                        value,
+                    );
+                }
+            }
+        }
+        let multiplicity = coefficients.keys().map(|k| k.2 + 1).max().unwrap_or(0);
+        Coupling { a, b, c, multiplicity, coefficients }
+    }
+
+    /// Number of times `c` occurs in `a ⊗ b`.
+    #[inline]
+    pub fn multiplicity(&self) -> usize {
+        self.multiplicity
+    }
+
+    /// `⟨a sa; b sb|c ρ sc⟩`
+    pub fn clebsch_gordan(
+        &self,
+        sa: State,
+        sb: State,
+        rho: usize,
+        sc: State,
+    ) -> SignedSqrt
+    {
+        self.coefficients.get(&(sa, sb, rho, sc)).cloned().unwrap_or_default()
+    }
+
+    /// Isoscalar factor `⟨a Y1 I1; b Y2 I2‖c ρ Y I⟩`, where the arguments
+    /// are pairs `(3 Y, 2 I)`, such that
+    ///
+    /// ```text
+    /// ⟨a Y1 I1 I1z; b Y2 I2 I2z|c ρ Y I Iz⟩
+    ///     = ⟨a Y1 I1; b Y2 I2‖c ρ Y I⟩ ⟨I1 I1z I2 I2z|I Iz⟩
+    /// ```
+    pub fn isoscalar_factor(
+        &self,
+        (y31, ti1): (i32, i32),
+        (y32, ti2): (i32, i32),
+        rho: usize,
+        (y3, ti): (i32, i32),
+    ) -> SignedSqrt
+    {
+        for tm1 in (-ti1 .. ti1 + 1).step_by(2) {
+            for tm2 in (-ti2 .. ti2 + 1).step_by(2) {
+                let cg = ClebschGordan {
+                    tj1: ti1,
+                    tm1,
+                    tj2: ti2,
+                    tm2,
+                    tj12: ti,
+                    tm12: tm1 + tm2,
+                }.value();
+                if cg.sign() == Ordering::Equal {
+                    continue;
+                }
+                let su3 = self.clebsch_gordan(
+                    State { y3: y31, ti: ti1, tm: tm1 },
+                    State { y3: y32, ti: ti2, tm: tm2 },
+                    rho,
+                    State { y3, ti, tm: tm1 + tm2 },
+                );
+                return SignedSqrt(su3.signed_sq() / cg.signed_sq());
+            }
+        }
+        Default::default()
+    }
+}
+
+/// `K_± = L_± / √2`, where `L_+ = √2 (E21 + E13)` and
+/// `L_− = √2 (E12 + E31)`.
+fn ladder<S: Space>(
+    space: &S,
+    raise: bool,
+    v: &Vector<S::Key>,
+) -> Vector<S::Key>
+{
+    let (op1, op2) = if raise {
+        (Op::E21, Op::E13)
+    } else {
+        (Op::E12, Op::E31)
+    };
+    let mut w = space.apply(op1, v);
+    axpy(&mut w, &Rational::from(1), &space.apply(op2, v));
+    w
+}
+
+/// Unnormalized states `|K L M⟩` of the Elliott basis, keyed by the state
+/// and `2 M`, with their norms.
+type ElliottStates<K> = HashMap<(ElliottState, i32), (Vector<K>, Rational)>;
+
+/// Construct the states of the Elliott basis of the representation with the
+/// Gelfand–Tsetlin states `gt`.
+///
+/// `|K L L⟩` is `K_+^(L − K)` applied to the projection of `χ_K` onto `L`,
+/// after removing the components along the states of larger `K`, and the
+/// other states are obtained with `K_−`.
+fn elliott<S: Space>(
+    space: &S,
+    irrep: Irrep,
+    gt: &GtStates<S::Key>,
+) -> ElliottStates<S::Key>
+{
+    let top = irrep.lambda + irrep.mu;
+    let low = irrep.lambda.min(irrep.mu);
+    // states with M = L, for each L
+    let mut stretched: BTreeMap<i32, Vec<_>> = BTreeMap::new();
+    for k in (low % 2 .. low + 1).step_by(2).rev() {
+        let pattern = irrep.intrinsic(k);
+        let chi = match gt.patterns.iter().position(|&p| p == pattern) {
+            Some(i) => &gt.vectors[i],
+            None => continue,
+        };
+        for l in k .. top + 1 {
+            // L² = K (K + 1) + 2 K_− K_+ on states with L_z = K
+            let mut v = chi.clone();
+            for l2 in (k .. top + 1).filter(|&l2| l2 != l) {
+                let mut w = ladder(space, false, &ladder(space, true, &v));
+                for x in w.values_mut() {
+                    *x *= 2;
+                }
+                let shift = Rational::from(k * (k + 1) - l2 * (l2 + 1));
+                axpy(&mut w, &shift, &v);
+                let denom = Rational::from(l * (l + 1) - l2 * (l2 + 1));
+                v = w.into_iter().map(|(key, x)| (key, x / &denom)).collect();
+            }
+            for _ in k .. l {
+                v = ladder(space, true, &v);
+            }
+            let previous = stretched.entry(l).or_default();
+            for (_, u) in previous.iter() {
+                let c = -space.dot(u, &v) / space.dot(u, u);
+                axpy(&mut v, &c, u);
+            }
+            if !v.is_empty() {
+                previous.push((k, v));
+            }
+        }
+    }
+    let mut states = HashMap::new();
+    for (l, vectors) in stretched {
+        for (k, mut v) in vectors {
+            let state = ElliottState { tk: 2 * k, tl: 2 * l };
+            for m in (-l .. l + 1).rev() {
+                let next = ladder(space, false, &v);
+                let norm = space.dot(&v, &v);
+                states.insert((state, 2 * m), (v, norm));
+                v = next;
+            }
+        }
+    }
+    states
+}
+
+/// `⟨u1 u2|w⟩` for the normalized states of the factors and of the product.
+fn overlap(
+    product: &Product,
+    (u1, n1): &(Vector<usize>, Rational),
+    (u2, n2): &(Vector<usize>, Rational),
+    (w, nw): &(Vector<(usize, usize)>, Rational),
+) -> SignedSqrt
+{
+    let mut x = Rational::new();
+    for (&(s1, s2), wx) in w {
+        if let (Some(y1), Some(y2)) = (u1.get(&s1), u2.get(&s2)) {
+            x += Rational::from(y1 * y2) * wx * product.norm(&(s1, s2));
+        }
+    }
+    let sq = Rational::from(&x * &x) / n1 / n2 / nw;
+    SignedSqrt(if x < 0 { -sq } else { sq })
+}
+
+/// Coupling coefficients for `a ⊗ b → c` in the Elliott basis.
+///
+/// The copies of `c` are labeled by `ρ` as in `Coupling`.  The states of
+/// the Elliott basis are orthogonalized in the order of decreasing `K`: the
+/// state `|K L L⟩` is proportional, with a positive factor, to
+///
+/// ```text
+/// K_+^(L − K) P_L χ_K
+/// ```
+///
+/// minus its components along the states of larger `K`, where `P_L`
+/// projects onto `L` and the intrinsic state `χ_K` is the Gelfand–Tsetlin
+/// state of `L_z = K` with the largest (if `λ ≥ μ`) or smallest (if `λ < μ`)
+/// weight `n_1`.  The components of the fundamental representation are the
+/// spherical components `m = 0, +1, −1`, and the states of different `M`
+/// follow Condon–Shortley.
+#[derive(Clone, Debug)]
+pub struct ElliottCoupling {
+    pub a: Irrep,
+    pub b: Irrep,
+    pub c: Irrep,
+    multiplicity: usize,
+    coefficients: HashMap<(ElliottState, ElliottState, usize, ElliottState),
+                          SignedSqrt>,
+}
+
+impl ElliottCoupling {
+    pub fn new(a: Irrep, b: Irrep, c: Irrep) -> Self {
+        let product = Product::new(a, b);
+        let hws = product.highest_weights(a, b, c);
+        let multiplicity = hws.len();
+        let [ref pa, ref pb] = product.factors;
+        let ea = elliott(pa, a, &pa.gt_states());
+        let eb = elliott(pb, b, &pb.gt_states());
+        let mut coefficients = HashMap::new();
+        for (rho, v) in hws.into_iter().enumerate() {
+            let gc = gelfand_tsetlin(&product, c, v);
+            // the reduced coefficients follow from the states with M = L and
+            // any M1 with a nonzero SU(2) coefficient
+            let ec = elliott(&product, c, &gc);
+            for (&(sc, tm), w) in ec.iter().filter(|e| (e.0).1 == (e.0).0.tl) {
+                for (&(sa, tm1), u1) in &ea {
+                    for (&(sb, tm2), u2) in &eb {
+                        let key = (sa, sb, rho, sc);
+                        if tm1 + tm2 != tm || coefficients.contains_key(&key) {
+                            continue;
+                        }
+                        let cg = ClebschGordan {
+                            tj1: sa.tl,
+                            tm1,
+                            tj2: sb.tl,
+                            tm2,
+                            tj12: sc.tl,
+                            tm12: tm,
+                        }.value();
+                        if cg.sign() == Ordering::Equal {
+                            continue;
+                        }
+                        let x = overlap(&product, u1, u2, w);
+                        coefficients.insert(
+                            key, SignedSqrt(x.signed_sq() / cg.signed_sq()));
+                    }
+                }
+            }
+        }
+        ElliottCoupling { a, b, c, multiplicity, coefficients }
+    }
+
+    /// Number of times `c` occurs in `a ⊗ b`.
+    #[inline]
+    pub fn multiplicity(&self) -> usize {
+        self.multiplicity
+    }
+
+    /// Reduced coefficient `⟨a K1 L1; b K2 L2‖c ρ K L⟩` such that
+    ///
+    /// ```text
+    /// ⟨a K1 L1 M1; b K2 L2 M2|c ρ K L M⟩
+    ///     = ⟨a K1 L1; b K2 L2‖c ρ K L⟩ ⟨L1 M1 L2 M2|L M⟩
+    /// ```
+    pub fn reduced(
+        &self,
+        sa: ElliottState,
+        sb: ElliottState,
+        rho: usize,
+        sc: ElliottState,
+    ) -> SignedSqrt
+    {
+        self.coefficients.get(&(sa, sb, rho, sc)).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,99 @@
+//! Construction of tables of symbols indexed by canonical Regge keys.
+
+use std::{cmp, thread};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use super::{SignedSqrt, Wigner6j};
+use super::regge::{CanonicalRegge3jm, CanonicalRegge6j};
+
+/// Number of entries a worker claims at a time.
+const CHUNK_LEN: usize = 256;
+
+/// Fill a table of length `len` by calling `compute` on every index, using
+/// up to `threads` worker threads (or as many as the machine has if zero).
+///
+/// The indices are handed out in chunks so that expensive regions of the
+/// table do not hold up a single thread.  After each chunk, `progress` is
+/// called with the number of entries completed so far and the total.  The
+/// result does not depend on the number of threads.
+pub fn build_table<V: Clone + Default + Send>(
+    len: usize,
+    threads: usize,
+    progress: &(dyn Fn(usize, usize) + Sync),
+    compute: &(dyn Fn(usize) -> V + Sync),
+) -> Vec<V>
+{
+    let threads = if threads == 0 {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        threads
+    };
+    let threads = cmp::max(1, cmp::min(threads, len.div_ceil(CHUNK_LEN)));
+    let mut table = vec![V::default(); len];
+    let chunks = Mutex::new(table.chunks_mut(CHUNK_LEN).enumerate());
+    let done = AtomicUsize::new(0);
+    let work = || loop {
+        let next = chunks.lock().unwrap().next();
+        let (i, chunk) = match next {
+            Some(x) => x,
+            None => break,
+        };
+        let offset = i * CHUNK_LEN;
+        for (j, entry) in chunk.iter_mut().enumerate() {
+            *entry = compute(offset + j);
+        }
+        let count = done.fetch_add(chunk.len(), Ordering::SeqCst) + chunk.len();
+        progress(count, len);
+    };
+    thread::scope(|scope| {
+        for _ in 1 .. threads {
+            scope.spawn(work);
+        }
+        work();
+    });
+    table
+}
+
+/// Build the table of canonical 3-jm values for angular momenta up to
+/// `tj_max`, indexed by `CanonicalRegge3jm::index`.
+///
+/// Entries at holes of the table are zero.  See `build_table` for the
+/// meaning of `threads` and `progress`.
+pub fn build_3jm_table(
+    tj_max: i32,
+    threads: usize,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Vec<SignedSqrt>
+{
+    let len = CanonicalRegge3jm::<u32>::checked_len(tj_max)
+        .expect("tj_max too large");
+    build_table(len, threads, progress, &|i| {
+        let regge = CanonicalRegge3jm::<u32>::checked_from_index(i)
+            .expect("index in range");
+        if regge.is_canonical() {
+            regge.smallest_representative().value()
+        } else {
+            Default::default()
+        }
+    })
+}
+
+/// Build the table of canonical 6-j values for angular momenta up to
+/// `tj_max`, indexed by `CanonicalRegge6j::index`.
+///
+/// See `build_table` for the meaning of `threads` and `progress`.
+pub fn build_6j_table(
+    tj_max: i32,
+    threads: usize,
+    progress: &(dyn Fn(usize, usize) + Sync),
+) -> Vec<SignedSqrt>
+{
+    let len = CanonicalRegge6j::<u32>::checked_len(tj_max)
+        .expect("tj_max too large");
+    build_table(len, threads, progress, &|i| {
+        Wigner6j::from(
+            CanonicalRegge6j::<u32>::checked_from_index(i)
+                .expect("index in range"),
+        ).value()
+    })
+}
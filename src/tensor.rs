@@ -0,0 +1,322 @@
+//! Matrix elements of spherical tensor operators.
+
+use std::cmp::Ordering;
+use std::ops::Mul;
+use rug::Rational;
+use super::{ClebschGordan, SignedSqrt, Wigner3jm, Wigner6j, Wigner9j};
+use super::internal::{get_tms, phase};
+
+/// Normalization of reduced matrix elements `⟨j'‖T^k‖j⟩`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Convention {
+    /// Edmonds (also Racah, Varshalovich):
+    ///
+    /// ```text
+    /// ⟨j' m'|T^k_q|j m⟩ = (−1)^(j' − m') (j' k j; −m' q m) ⟨j'‖T^k‖j⟩
+    /// ```
+    Edmonds,
+    /// Brink–Satchler (also Condon–Shortley, Rose):
+    ///
+    /// ```text
+    /// ⟨j' m'|T^k_q|j m⟩ = ⟨j m k q|j k j' m'⟩ ⟨j'‖T^k‖j⟩
+    /// ```
+    BrinkSatchler,
+}
+
+/// Wigner–Eckart theorem for `⟨j'‖T^k‖j⟩`, where `tj1` is `2 j'` and `tj2`
+/// is `2 j`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WignerEckart {
+    pub tj1: i32,
+    pub tk: i32,
+    pub tj2: i32,
+    pub convention: Convention,
+}
+
+impl WignerEckart {
+    /// The geometric factor `⟨j' m'|T^k_q|j m⟩ / ⟨j'‖T^k‖j⟩`.
+    pub fn coefficient(self, tm1: i32, tq: i32, tm2: i32) -> SignedSqrt {
+        match self.convention {
+            Convention::Edmonds => {
+                phase((self.tj1 - tm1) / 2) * Wigner3jm {
+                    tj1: self.tj1,
+                    tm1: -tm1,
+                    tj2: self.tk,
+                    tm2: tq,
+                    tj3: self.tj2,
+                    tm3: tm2,
+                }.value()
+            }
+            Convention::BrinkSatchler => ClebschGordan {
+                tj1: self.tj2,
+                tm1: tm2,
+                tj2: self.tk,
+                tm2: tq,
+                tj12: self.tj1,
+                tm12: tm1,
+            }.value(),
+        }
+    }
+
+    /// Calculate `⟨j' m'|T^k_q|j m⟩` from the reduced matrix element.
+    pub fn matrix_element(
+        self,
+        reduced: SignedSqrt,
+        tm1: i32,
+        tq: i32,
+        tm2: i32,
+    ) -> SignedSqrt
+    {
+        self.coefficient(tm1, tq, tm2) * reduced
+    }
+
+    /// Calculate the reduced matrix element from `⟨j' m'|T^k_q|j m⟩`, or
+    /// `None` if the geometric factor vanishes for these projections.
+    pub fn reduced(
+        self,
+        element: SignedSqrt,
+        tm1: i32,
+        tq: i32,
+        tm2: i32,
+    ) -> Option<SignedSqrt>
+    {
+        let c = self.coefficient(tm1, tq, tm2);
+        if c.sign() == Ordering::Equal {
+            return None;
+        }
+        Some(SignedSqrt(element.signed_sq() / c.signed_sq()))
+    }
+
+    /// Convert a reduced matrix element to another convention.
+    ///
+    /// ```text
+    /// ⟨j'‖T^k‖j⟩_Edmonds = (−1)^(2 k) √(2 j' + 1) ⟨j'‖T^k‖j⟩_BrinkSatchler
+    /// ```
+    pub fn convert(
+        self,
+        reduced: SignedSqrt,
+        convention: Convention,
+    ) -> SignedSqrt
+    {
+        let c = phase(self.tk) * SignedSqrt((self.tj1 + 1).into());
+        match (self.convention, convention) {
+            (Convention::BrinkSatchler, Convention::Edmonds) => c * reduced,
+            (Convention::Edmonds, Convention::BrinkSatchler) => {
+                SignedSqrt(reduced.signed_sq() / c.signed_sq())
+            }
+            _ => reduced,
+        }
+    }
+
+    /// Matrix of `T^k_q` between the `|j m⟩` states, with rows indexed by
+    /// `m'` and columns by `m`, both in increasing order.
+    pub fn matrix(self, reduced: SignedSqrt, tq: i32) -> Vec<Vec<SignedSqrt>> {
+        get_tms(self.tj1).map(|tm1| {
+            get_tms(self.tj2).map(|tm2| {
+                self.matrix_element(reduced.clone(), tm1, tq, tm2)
+            }).collect()
+        }).collect()
+    }
+}
+
+/// Value of a reduced matrix element.
+pub trait Reduced: Sized + Mul<Output = Self> {
+    /// Multiply by a geometric factor.
+    fn scale(self, c: SignedSqrt) -> Self;
+}
+
+impl Reduced for SignedSqrt {
+    fn scale(self, c: SignedSqrt) -> Self {
+        self * c
+    }
+}
+
+impl Reduced for f64 {
+    fn scale(self, c: SignedSqrt) -> Self {
+        self * f64::from(c)
+    }
+}
+
+/// State `|(j1 j2) j⟩` of a system composed of two subsystems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CoupledState {
+    pub tj1: i32,
+    pub tj2: i32,
+    pub tj: i32,
+}
+
+/// Reduced matrix elements `⟨bra‖X^k‖ket⟩` of operators on a composite
+/// system, in terms of reduced matrix elements of operators `T^k1` acting on
+/// the first subsystem and `U^k2` acting on the second.
+///
+/// Both the inputs and the outputs use `convention`.  The formulas are
+/// those of Edmonds (1957), section 7.1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Composite {
+    pub convention: Convention,
+    pub bra: CoupledState,
+    pub ket: CoupledState,
+}
+
+impl Composite {
+    /// Factor `c` such that `⟨j'‖T^k‖j⟩` in the given convention is `c`
+    /// times its value in the Edmonds convention.
+    fn convention_factor(self, tj1: i32, tk: i32) -> SignedSqrt {
+        match self.convention {
+            Convention::Edmonds => 1.into(),
+            Convention::BrinkSatchler => {
+                SignedSqrt(Rational::from((phase(tk), tj1 + 1)))
+            }
+        }
+    }
+
+    /// Inverse of `convention_factor`.
+    fn edmonds_factor(self, tj1: i32, tk: i32) -> SignedSqrt {
+        SignedSqrt(self.convention_factor(tj1, tk).signed_sq().recip())
+    }
+
+    /// `⟨(j1 j2) j‖[T^k1 ⊗ U^k2]^k‖(j1' j2') j'⟩` from `t = ⟨j1‖T^k1‖j1'⟩`
+    /// and `u = ⟨j2‖U^k2‖j2'⟩`.
+    pub fn tensor_product<T: Reduced>(
+        self,
+        tk1: i32,
+        tk2: i32,
+        tk: i32,
+        t: T,
+        u: T,
+    ) -> T
+    {
+        let (b, k) = (self.bra, self.ket);
+        let c = SignedSqrt(
+            Rational::from(b.tj + 1)
+                * Rational::from(k.tj + 1)
+                * Rational::from(tk + 1),
+        ) * Wigner9j {
+            tj1: b.tj1,
+            tj2: k.tj1,
+            tj3: tk1,
+            tj4: b.tj2,
+            tj5: k.tj2,
+            tj6: tk2,
+            tj7: b.tj,
+            tj8: k.tj,
+            tj9: tk,
+        }.value();
+        let c = c * self.edmonds_factor(b.tj1, tk1) * self.edmonds_factor(b.tj2, tk2)
+            * self.convention_factor(b.tj, tk);
+        (t * u).scale(c)
+    }
+
+    /// `⟨(j1 j2) j‖T^k‖(j1' j2') j'⟩` from `t = ⟨j1‖T^k‖j1'⟩`, where `T`
+    /// acts only on the first subsystem.
+    pub fn first<T: Reduced>(self, tk: i32, t: T) -> T {
+        let (b, k) = (self.bra, self.ket);
+        if b.tj2 != k.tj2 {
+            return t.scale(Default::default());
+        }
+        let c = phase((b.tj1 + b.tj2 + k.tj + tk) / 2)
+            * SignedSqrt(Rational::from(b.tj + 1) * Rational::from(k.tj + 1))
+            * Wigner6j {
+                tj1: b.tj1,
+                tj2: b.tj,
+                tj3: b.tj2,
+                tj4: k.tj,
+                tj5: k.tj1,
+                tj6: tk,
+            }.value();
+        let c = c * self.edmonds_factor(b.tj1, tk)
+            * self.convention_factor(b.tj, tk);
+        t.scale(c)
+    }
+
+    /// `⟨(j1 j2) j‖U^k‖(j1' j2') j'⟩` from `u = ⟨j2‖U^k‖j2'⟩`, where `U`
+    /// acts only on the second subsystem.
+    pub fn second<T: Reduced>(self, tk: i32, u: T) -> T {
+        let (b, k) = (self.bra, self.ket);
+        if b.tj1 != k.tj1 {
+            return u.scale(Default::default());
+        }
+        let c = phase((b.tj1 + k.tj2 + b.tj + tk) / 2)
+            * SignedSqrt(Rational::from(b.tj + 1) * Rational::from(k.tj + 1))
+            * Wigner6j {
+                tj1: b.tj2,
+                tj2: b.tj,
+                tj3: b.tj1,
+                tj4: k.tj,
+                tj5: k.tj2,
+                tj6: tk,
+            }.value();
+        let c = c * self.edmonds_factor(b.tj2, tk)
+            * self.convention_factor(b.tj, tk);
+        u.scale(c)
+    }
+
+    /// Matrix element `⟨(j1 j2) j m|T^k · U^k|(j1' j2') j' m⟩`, which does
+    /// not depend on `m`, from `t = ⟨j1‖T^k‖j1'⟩` and `u = ⟨j2‖U^k‖j2'⟩`.
+    pub fn scalar_product<T: Reduced>(self, tk: i32, t: T, u: T) -> T {
+        let (b, k) = (self.bra, self.ket);
+        if b.tj != k.tj {
+            return (t * u).scale(Default::default());
+        }
+        let c = phase((k.tj1 + b.tj2 + b.tj) / 2) * Wigner6j {
+            tj1: b.tj,
+            tj2: b.tj2,
+            tj3: b.tj1,
+            tj4: tk,
+            tj5: k.tj1,
+            tj6: k.tj2,
+        }.value();
+        let c = c * self.edmonds_factor(b.tj1, tk) * self.edmonds_factor(b.tj2, tk);
+        (t * u).scale(c)
+    }
+}
+
+/// Reduced matrix element `⟨l‖C^k‖l'⟩` of the Racah tensor
+/// `C^k_q = √(4π / (2 k + 1)) Y^k_q` in the Edmonds convention.
+///
+/// ```text
+/// ⟨l‖C^k‖l'⟩ = (−1)^l √((2 l + 1) (2 l' + 1)) (l k l'; 0 0 0)
+/// ```
+pub fn racah_tensor(tl1: i32, tk: i32, tl2: i32) -> SignedSqrt {
+    phase(tl1 / 2)
+        * SignedSqrt(Rational::from(tl1 + 1) * Rational::from(tl2 + 1))
+        * Wigner3jm {
+            tj1: tl1,
+            tm1: 0,
+            tj2: tk,
+            tm2: 0,
+            tj3: tl2,
+            tm3: 0,
+        }.value()
+}
+
+/// Reduced matrix element `⟨j‖J‖j'⟩` of an angular momentum operator (such
+/// as `J`, `L`, or `S`) in the Edmonds convention.
+///
+/// ```text
+/// ⟨j‖J‖j'⟩ = δ(j, j') √(j (j + 1) (2 j + 1))
+/// ```
+pub fn angular_momentum(tj1: i32, tj2: i32) -> SignedSqrt {
+    if tj1 != tj2 {
+        return Default::default();
+    }
+    SignedSqrt(Rational::from((tj1 * (tj1 + 2) * (tj1 + 1), 4)))
+}
+
+/// Reduced matrix element `⟨l‖Y^k‖l'⟩` of a spherical harmonic in the
+/// Edmonds convention, in units of `1 / √(4π)` (which cannot be represented
+/// exactly).
+///
+/// ```text
+/// ⟨l‖Y^k‖l'⟩ = √((2 k + 1) / (4π)) ⟨l‖C^k‖l'⟩
+/// ```
+pub fn spherical_harmonic(tl1: i32, tk: i32, tl2: i32) -> SignedSqrt {
+    SignedSqrt((tk + 1).into()) * racah_tensor(tl1, tk, tl2)
+}
+
+/// Reduced matrix element `⟨l‖Y^k‖l'⟩` of a spherical harmonic in the
+/// Edmonds convention, including the `1 / √(4π)` factor.
+pub fn spherical_harmonic_f64(tl1: i32, tk: i32, tl2: i32) -> f64 {
+    f64::from(spherical_harmonic(tl1, tk, tl2))
+        / (4.0 * ::std::f64::consts::PI).sqrt()
+}
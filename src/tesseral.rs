@@ -0,0 +1,180 @@
+//! Coupling coefficients in the basis of real (tesseral) spherical
+//! harmonics, for integer angular momenta.
+//!
+//! The real harmonics are related to the complex ones by
+//!
+//! ```text
+//! S_l0  = Y_l0
+//! S_lμ  = (Y_l,−μ + (−1)^μ Y_lμ) / √2
+//! S_l,−μ = i (Y_l,−μ − (−1)^μ Y_lμ) / √2
+//! ```
+//!
+//! for `μ > 0`.  As in the rest of the crate, `tm` denotes `2 μ`.
+
+use std::cmp::Ordering;
+use rug::Rational;
+use super::{ClebschGordan, SignedSqrt, Wigner3jm};
+pub use super::RealOrImaginary;
+use super::internal::{phase, sum_radicals};
+use super::tensor::racah_tensor;
+
+/// Entries `(tm, c, p)` of the row of the unitary transform, such that
+/// `S_lμ = Σ c i^p Y_lm`.
+fn transform(tl: i32, tmu: i32) -> Vec<(i32, SignedSqrt, i32)> {
+    if tmu.abs() > tl || tl % 2 != 0 || tmu % 2 != 0 {
+        return Vec::new();
+    }
+    let half = || SignedSqrt(Rational::from((1, 2)));
+    let mu = tmu.abs() / 2;
+    match tmu.cmp(&0) {
+        Ordering::Equal => vec![(0, 1.into(), 0)],
+        Ordering::Greater => vec![
+            (-tmu, half(), 0),
+            (tmu, phase(mu) * half(), 0),
+        ],
+        Ordering::Less => vec![
+            (tmu, half(), 1),
+            (-tmu, -phase(mu) * half(), 1),
+        ],
+    }
+}
+
+/// Sum `Σ c i^p` of terms `(c, p)`, which must be either real or purely
+/// imaginary.
+fn sum(terms: Vec<(SignedSqrt, i32)>) -> RealOrImaginary {
+    let (mut re, mut im) = (Vec::new(), Vec::new());
+    for (c, p) in terms {
+        let c = if p.rem_euclid(4) >= 2 { -1 * c } else { c };
+        if p % 2 == 0 { re.push(c) } else { im.push(c) }
+    }
+    let re = sum_radicals(re).expect("sum of commensurate terms");
+    let im = sum_radicals(im).expect("sum of commensurate terms");
+    if im.sign() == Ordering::Equal {
+        RealOrImaginary { value: re, imaginary: false }
+    } else {
+        assert_eq!(re.sign(), Ordering::Equal, "neither real nor imaginary");
+        RealOrImaginary { value: im, imaginary: true }
+    }
+}
+
+/// Clebsch-Gordan coefficient in the real basis
+///
+/// ```text
+/// ⟨l1 l2 μ1 μ2|l1 l2 l12 μ12⟩
+/// ```
+///
+/// where the states transform like the real harmonics `S_lμ`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RealClebschGordan {
+    pub tl1: i32,
+    pub tm1: i32,
+    pub tl2: i32,
+    pub tm2: i32,
+    pub tl12: i32,
+    pub tm12: i32,
+}
+
+impl RealClebschGordan {
+    pub fn value(self) -> RealOrImaginary {
+        let mut terms = Vec::new();
+        for (tm1, c1, p1) in transform(self.tl1, self.tm1) {
+            for (tm2, c2, p2) in transform(self.tl2, self.tm2) {
+                for (tm12, c12, p12) in transform(self.tl12, self.tm12) {
+                    let cg = ClebschGordan {
+                        tj1: self.tl1,
+                        tm1,
+                        tj2: self.tl2,
+                        tm2,
+                        tj12: self.tl12,
+                        tm12,
+                    }.value();
+                    // the bra is complex conjugated
+                    terms.push((c1.clone() * c2.clone() * c12 * cg, p12 - p1 - p2));
+                }
+            }
+        }
+        sum(terms)
+    }
+}
+
+/// Wigner 3-jm symbol in the real basis
+///
+/// ```text
+/// Σ[m1 m2 m3] U(μ1, m1) U(μ2, m2) U(μ3, m3) (l1 l2 l3; m1 m2 m3)
+/// ```
+///
+/// where `U` is the transform from complex to real harmonics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RealWigner3jm {
+    pub tl1: i32,
+    pub tm1: i32,
+    pub tl2: i32,
+    pub tm2: i32,
+    pub tl3: i32,
+    pub tm3: i32,
+}
+
+impl RealWigner3jm {
+    pub fn value(self) -> RealOrImaginary {
+        let mut terms = Vec::new();
+        for (tm1, c1, p1) in transform(self.tl1, self.tm1) {
+            for (tm2, c2, p2) in transform(self.tl2, self.tm2) {
+                for (tm3, c3, p3) in transform(self.tl3, self.tm3) {
+                    let w = Wigner3jm {
+                        tj1: self.tl1,
+                        tm1,
+                        tj2: self.tl2,
+                        tm2,
+                        tj3: self.tl3,
+                        tm3,
+                    }.value();
+                    terms.push((c1.clone() * c2.clone() * c3 * w, p1 + p2 + p3));
+                }
+            }
+        }
+        sum(terms)
+    }
+}
+
+/// Gaunt coefficient of real harmonics
+///
+/// ```text
+/// ∫ S_l1μ1 S_l2μ2 S_l3μ3 dΩ
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RealGaunt {
+    pub tl1: i32,
+    pub tm1: i32,
+    pub tl2: i32,
+    pub tm2: i32,
+    pub tl3: i32,
+    pub tm3: i32,
+}
+
+impl RealGaunt {
+    /// Value in units of `1 / √(4π)` (which cannot be represented exactly).
+    ///
+    /// ```text
+    /// ∫ S1 S2 S3 dΩ = √((2 l1 + 1) (2 l2 + 1) (2 l3 + 1) / (4π))
+    ///                 × (l1 l2 l3; 0 0 0) (l1 l2 l3; μ1 μ2 μ3)_real
+    /// ```
+    pub fn value(self) -> SignedSqrt {
+        let w = RealWigner3jm {
+            tl1: self.tl1,
+            tm1: self.tm1,
+            tl2: self.tl2,
+            tm2: self.tm2,
+            tl3: self.tl3,
+            tm3: self.tm3,
+        }.value();
+        // the real 3-jm symbol is imaginary only if l1 + l2 + l3 is odd, in
+        // which case the reduced matrix element vanishes
+        phase(self.tl1 / 2) * SignedSqrt((self.tl3 + 1).into())
+            * racah_tensor(self.tl1, self.tl3, self.tl2) * w.re()
+    }
+
+    /// Value including the `1 / √(4π)` factor.
+    pub fn value_f64(self) -> f64 {
+        f64::from(self.value()) / (4.0 * ::std::f64::consts::PI).sqrt()
+    }
+}
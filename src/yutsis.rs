@@ -0,0 +1,774 @@
+//! Reduction of Yutsis graphs to 6-j, 9-j, and 12-j symbols.
+//!
+//! A Yutsis graph is a closed network of 3-jm symbols in which every
+//! projection is summed over.  Each vertex is a 3-jm symbol and each edge is
+//! an angular momentum shared by two of them.  The value of the graph is
+//!
+//! ```text
+//! Σ[m] ∏[edges] (−1)^(j − m) ∏[vertices] (j1 j2 j3; μ1 μ2 μ3)
+//! ```
+//!
+//! where `μ = +m` at the tail of the edge and `μ = −m` at its head.
+
+use std::{error, fmt};
+use std::cmp::Ordering;
+use rug::Rational;
+use super::{SignedSqrt, Wigner3jm, Wigner6j, Wigner9j, Wigner12jSecond};
+use super::internal::{get_tms, phase, triangle_condition};
+
+/// Edge of a Yutsis graph, directed from `tail` to `head`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Edge {
+    pub tj: i32,
+    pub tail: usize,
+    pub head: usize,
+}
+
+/// Vertex of a Yutsis graph.
+///
+/// The vertex represents the 3-jm symbol whose columns are `edges` in order
+/// if `sign` is `+1` (counterclockwise), or in the reverse cyclic order if
+/// `sign` is `−1` (clockwise).  If an edge forms a loop, its first
+/// occurrence is its tail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vertex {
+    pub edges: [usize; 3],
+    pub sign: i32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct YutsisGraph {
+    pub edges: Vec<Edge>,
+    pub vertices: Vec<Vertex>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum YutsisError {
+    /// The edges and vertices are inconsistent with each other.
+    Malformed,
+    /// None of the reduction rules apply to the graph.
+    Irreducible,
+}
+
+impl fmt::Display for YutsisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            YutsisError::Malformed => write!(f, "malformed Yutsis graph"),
+            YutsisError::Irreducible => write!(f, "irreducible Yutsis graph"),
+        }
+    }
+}
+
+impl error::Error for YutsisError {}
+
+/// Symbol appearing in the reduced form of a graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Symbol {
+    Wigner6j(Wigner6j),
+    Wigner9j(Wigner9j),
+    Wigner12jSecond(Wigner12jSecond),
+}
+
+impl Symbol {
+    pub fn value(self) -> SignedSqrt {
+        match self {
+            Symbol::Wigner6j(w) => w.value(),
+            Symbol::Wigner9j(w) => w.value(),
+            Symbol::Wigner12jSecond(w) => w.value(),
+        }
+    }
+}
+
+/// Product of a coefficient (which includes the phases and the `(2 j + 1)`
+/// factors) and symbols.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Reduction {
+    pub coefficient: SignedSqrt,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Default for Reduction {
+    fn default() -> Self {
+        Reduction { coefficient: 1.into(), symbols: Vec::new() }
+    }
+}
+
+impl Reduction {
+    pub fn value(&self) -> SignedSqrt {
+        self.symbols.iter().fold(self.coefficient.clone(), |acc, symbol| {
+            acc * symbol.value()
+        })
+    }
+
+    fn mul_phase(&mut self, p: i32) {
+        if p < 0 {
+            self.coefficient = -1 * self.coefficient.clone();
+        }
+    }
+
+    fn mul(&mut self, c: SignedSqrt) {
+        self.coefficient = self.coefficient.clone() * c;
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficient.sign() == Ordering::Equal
+    }
+
+    fn set_zero(&mut self) {
+        self.coefficient = Default::default();
+        self.symbols.clear();
+    }
+}
+
+impl YutsisGraph {
+    /// Reduce the graph to a product of 6-j, 9-j, and 12-j symbols.
+    ///
+    /// Loops, lines with zero angular momentum, bubbles, triangles, and
+    /// more generally 1-, 2-, and 3-line cuts are removed until only
+    /// tetrahedra (6-j), the complete bipartite graph `K_{3,3}` of the 9-j
+    /// symbol, and cubes of the 12-j symbol of the second kind remain.  (A
+    /// triangular prism is not irreducible: its 3-line cut splits it into
+    /// two 6-j symbols.)  Graphs that contain other irreducible pieces
+    /// cannot be handled.
+    pub fn reduce(&self) -> Result<Reduction, YutsisError> {
+        let graph = self.to_graph()?;
+        let mut reduction = Reduction::default();
+        reduce(graph, &mut reduction)?;
+        Ok(reduction)
+    }
+
+    /// Calculate the value via `reduce`.
+    pub fn value(&self) -> Result<SignedSqrt, YutsisError> {
+        self.reduce().map(|reduction| reduction.value())
+    }
+
+    /// Calculate the value by explicit summation over all projections.
+    ///
+    /// This is very slow and is mainly useful for testing.
+    pub fn value_by_summation(&self) -> Result<SignedSqrt, YutsisError> {
+        let graph = self.to_graph()?;
+        let mut tms = vec![0; graph.tjs.len()];
+        let mut sum = SignedSqrt::default();
+        let used: Vec<usize> = (0 .. graph.tjs.len())
+            .filter(|&e| graph.is_used(e))
+            .collect();
+        sum_projections(&graph, &used, 0, &mut tms, &mut sum);
+        Ok(sum)
+    }
+
+    fn to_graph(&self) -> Result<Graph, YutsisError> {
+        let mut counts = vec![(0, 0); self.edges.len()];
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        for (v, vertex) in self.vertices.iter().enumerate() {
+            let order = match vertex.sign {
+                1 => [0, 1, 2],
+                -1 => [0, 2, 1],
+                _ => return Err(YutsisError::Malformed),
+            };
+            let mut slots = [Slot { edge: 0, tail: false }; 3];
+            for k in 0 .. 3 {
+                let e = vertex.edges[k];
+                let edge = self.edges.get(e).ok_or(YutsisError::Malformed)?;
+                let tail = if edge.tail == v && edge.head == v {
+                    !vertex.edges[.. k].contains(&e)
+                } else if edge.tail == v {
+                    true
+                } else if edge.head == v {
+                    false
+                } else {
+                    return Err(YutsisError::Malformed);
+                };
+                if tail {
+                    counts[e].0 += 1;
+                } else {
+                    counts[e].1 += 1;
+                }
+                slots[order[k]] = Slot { edge: e, tail };
+            }
+            vertices.push(slots);
+        }
+        if counts.iter().any(|&c| c != (1, 1)) {
+            return Err(YutsisError::Malformed);
+        }
+        Ok(Graph {
+            tjs: self.edges.iter().map(|edge| edge.tj).collect(),
+            vertices,
+        })
+    }
+}
+
+/// Occurrence of an edge at a vertex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    edge: usize,
+    tail: bool,
+}
+
+/// Graph in which each vertex is the 3-jm symbol of its slots in order.
+/// Edges that are no longer referenced are ignored.
+#[derive(Clone, Debug)]
+struct Graph {
+    tjs: Vec<i32>,
+    vertices: Vec<[Slot; 3]>,
+}
+
+impl Graph {
+    fn tj(&self, slot: Slot) -> i32 {
+        self.tjs[slot.edge]
+    }
+
+    fn is_used(&self, e: usize) -> bool {
+        self.vertices.iter().any(|slots| slots.iter().any(|s| s.edge == e))
+    }
+
+    /// Find the (vertex, position) where the edge has its tail or head.
+    fn find(&self, e: usize, tail: bool) -> Option<(usize, usize)> {
+        for (v, slots) in self.vertices.iter().enumerate() {
+            for (k, slot) in slots.iter().enumerate() {
+                if slot.edge == e && slot.tail == tail {
+                    return Some((v, k));
+                }
+            }
+        }
+        None
+    }
+
+    /// Reverse the direction of an edge, returning the phase.
+    fn reverse(&mut self, e: usize) -> i32 {
+        for slots in &mut self.vertices {
+            for slot in slots.iter_mut() {
+                if slot.edge == e {
+                    slot.tail = !slot.tail;
+                }
+            }
+        }
+        phase(self.tjs[e])
+    }
+
+    /// Reorder the columns of a vertex so that the new `k`-th column is the
+    /// old `order[k]`-th column, returning the phase.
+    fn permute(&mut self, v: usize, order: [usize; 3]) -> i32 {
+        let old = self.vertices[v];
+        self.vertices[v] = [old[order[0]], old[order[1]], old[order[2]]];
+        if permutation_is_odd(order) {
+            phase((self.tj(old[0]) + self.tj(old[1]) + self.tj(old[2])) / 2)
+        } else {
+            1
+        }
+    }
+
+    fn subgraph(&self, keep: &[usize]) -> Graph {
+        Graph {
+            tjs: self.tjs.clone(),
+            vertices: keep.iter().map(|&v| self.vertices[v]).collect(),
+        }
+    }
+
+    fn components(&self) -> Vec<Vec<usize>> {
+        let n = self.vertices.len();
+        let mut component = vec![usize::MAX; n];
+        let mut components = Vec::new();
+        for start in 0 .. n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+            let c = components.len();
+            let mut members = vec![start];
+            component[start] = c;
+            let mut i = 0;
+            while i < members.len() {
+                let v = members[i];
+                i += 1;
+                for (w, cw) in component.iter_mut().enumerate() {
+                    if *cw == usize::MAX && self.adjacent(v, w) {
+                        *cw = c;
+                        members.push(w);
+                    }
+                }
+            }
+            components.push(members);
+        }
+        components
+    }
+
+    fn adjacent(&self, v: usize, w: usize) -> bool {
+        self.vertices[v].iter()
+            .any(|s| self.vertices[w].iter().any(|t| s.edge == t.edge))
+    }
+
+    /// Edges with exactly one occurrence among the given vertices.
+    fn boundary(&self, members: &[usize]) -> Vec<usize> {
+        let mut edges: Vec<usize> = Vec::new();
+        for &v in members {
+            for slot in &self.vertices[v] {
+                match edges.iter().position(|&e| e == slot.edge) {
+                    Some(i) => { edges.remove(i); }
+                    None => edges.push(slot.edge),
+                }
+            }
+        }
+        edges
+    }
+}
+
+fn permutation_is_odd(order: [usize; 3]) -> bool {
+    let mut inversions = 0;
+    for i in 0 .. 3 {
+        for j in i + 1 .. 3 {
+            if order[i] > order[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 != 0
+}
+
+fn reduce(
+    mut graph: Graph,
+    reduction: &mut Reduction,
+) -> Result<(), YutsisError>
+{
+    loop {
+        if reduction.is_zero() || graph.vertices.is_empty() {
+            return Ok(());
+        }
+        if graph.vertices.iter().any(|slots| {
+            !triangle_condition(
+                graph.tj(slots[0]),
+                graph.tj(slots[1]),
+                graph.tj(slots[2]),
+            )
+        }) {
+            reduction.set_zero();
+            return Ok(());
+        }
+        if let Some(v) = (0 .. graph.vertices.len()).find(|&v| {
+            let s = graph.vertices[v];
+            s[0].edge == s[1].edge || s[1].edge == s[2].edge
+                || s[0].edge == s[2].edge
+        }) {
+            remove_loop(&mut graph, v, reduction);
+            continue;
+        }
+        if let Some(e) = (0 .. graph.tjs.len())
+            .find(|&e| graph.tjs[e] == 0 && graph.is_used(e))
+        {
+            remove_zero_edge(&mut graph, e, reduction);
+            continue;
+        }
+        let components = graph.components();
+        if components.len() > 1 {
+            for members in components {
+                reduce(graph.subgraph(&members), reduction)?;
+            }
+            return Ok(());
+        }
+        if let Some((members, boundary)) = find_cut(&graph) {
+            return apply_cut(&mut graph, &members, &boundary, reduction);
+        }
+        return match_template(&graph, reduction);
+    }
+}
+
+/// Remove a vertex `(e e c)` where `e` is a loop, which forces `c` to
+/// vanish.
+fn remove_loop(graph: &mut Graph, v: usize, reduction: &mut Reduction) {
+    let s = graph.vertices[v];
+    let (i, j, k) = if s[0].edge == s[1].edge {
+        (0, 1, 2)
+    } else if s[1].edge == s[2].edge {
+        (1, 2, 0)
+    } else {
+        (0, 2, 1)
+    };
+    let (i, j) = if s[i].tail { (i, j) } else { (j, i) };
+    let p = graph.permute(v, [i, j, k]);
+    reduction.mul_phase(p);
+    let s = graph.vertices[v];
+    if graph.tj(s[2]) != 0 {
+        reduction.set_zero();
+        return;
+    }
+    // Σ[m] (−1)^(j − m) (j j 0; m −m 0) = √(2 j + 1)
+    reduction.mul(SignedSqrt((graph.tj(s[0]) + 1).into()));
+    graph.vertices.remove(v);
+    // the zero edge is left dangling at its other end
+    remove_zero_edge(graph, s[2].edge, reduction);
+}
+
+/// Remove an edge with zero angular momentum, merging the other two edges
+/// at each of its ends.
+fn remove_zero_edge(graph: &mut Graph, z: usize, reduction: &mut Reduction) {
+    while let Some(v) = (0 .. graph.vertices.len())
+        .find(|&v| graph.vertices[v].iter().any(|s| s.edge == z))
+    {
+        if reduction.is_zero() {
+            return;
+        }
+        let s = graph.vertices[v];
+        let k = (0 .. 3).find(|&k| s[k].edge == z).unwrap();
+        let (i, j) = match k {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1),
+        };
+        if s[i].edge == s[j].edge {
+            // a loop attached to a zero edge
+            let (i, j) = if s[i].tail { (i, j) } else { (j, i) };
+            let p = graph.permute(v, [i, j, k]);
+            reduction.mul_phase(p);
+            reduction.mul(SignedSqrt((graph.tj(s[i]) + 1).into()));
+            graph.vertices.remove(v);
+            continue;
+        }
+        // arrange for the edge a to enter and the edge b to leave
+        let (i, j) = match (s[i].tail, s[j].tail) {
+            (false, true) => (i, j),
+            (true, false) => (j, i),
+            (true, true) => {
+                let p = graph.reverse(s[i].edge);
+                reduction.mul_phase(p);
+                (i, j)
+            }
+            (false, false) => {
+                let p = graph.reverse(s[j].edge);
+                reduction.mul_phase(p);
+                (i, j)
+            }
+        };
+        let p = graph.permute(v, [i, j, k]);
+        reduction.mul_phase(p);
+        let s = graph.vertices[v];
+        let (a, b) = (s[0].edge, s[1].edge);
+        if graph.tjs[a] != graph.tjs[b] {
+            reduction.set_zero();
+            return;
+        }
+        // (ja jb 0; −m m 0) merges a and b into a single edge
+        reduction.mul(SignedSqrt(Rational::from((
+            phase(graph.tjs[a]),
+            graph.tjs[a] + 1,
+        ))));
+        graph.vertices.remove(v);
+        let (w, l) = graph.find(b, false).expect("head of edge");
+        graph.vertices[w][l] = Slot { edge: a, tail: false };
+    }
+}
+
+/// Find a set of vertices that is connected to the rest of the graph by at
+/// most three edges, excluding the trivial case of a single vertex.
+fn find_cut(graph: &Graph) -> Option<(Vec<usize>, Vec<usize>)> {
+    let n = graph.vertices.len();
+    for size in 2 .. n / 2 + 1 {
+        let mut members: Vec<usize> = (0 .. size).collect();
+        loop {
+            let boundary = graph.boundary(&members);
+            if boundary.len() <= 3 {
+                return Some((members, boundary));
+            }
+            // advance to the next combination
+            let mut i = size;
+            while i > 0 && members[i - 1] == n - size + i - 1 {
+                i -= 1;
+            }
+            if i == 0 {
+                break;
+            }
+            members[i - 1] += 1;
+            for k in i .. size {
+                members[k] = members[k - 1] + 1;
+            }
+        }
+    }
+    None
+}
+
+/// Factorize the graph across the edges connecting `members` to the rest.
+/// Bubbles and triangles are the simplest instances of 2- and 3-line cuts.
+fn apply_cut(
+    graph: &mut Graph,
+    members: &[usize],
+    boundary: &[usize],
+    reduction: &mut Reduction,
+) -> Result<(), YutsisError>
+{
+    // direct all cut edges away from the members
+    for &e in boundary {
+        let (v, _) = graph.find(e, true).expect("tail of edge");
+        if !members.contains(&v) {
+            let p = graph.reverse(e);
+            reduction.mul_phase(p);
+        }
+    }
+    let others: Vec<usize> = (0 .. graph.vertices.len())
+        .filter(|v| !members.contains(v))
+        .collect();
+    let mut inner = graph.subgraph(members);
+    let mut outer = graph.subgraph(&others);
+    match boundary.len() {
+        1 => {
+            // an isolated edge must have zero angular momentum, but those
+            // have already been removed
+            reduction.set_zero();
+            Ok(())
+        }
+        2 => {
+            let (e1, e2) = (boundary[0], boundary[1]);
+            if graph.tjs[e1] != graph.tjs[e2] {
+                reduction.set_zero();
+                return Ok(());
+            }
+            // join the two cut edges on either side
+            let (v, k) = inner.find(e2, true).expect("tail of edge");
+            inner.vertices[v][k] = Slot { edge: e1, tail: false };
+            let (v, k) = outer.find(e1, false).expect("head of edge");
+            outer.vertices[v][k] = Slot { edge: e1, tail: true };
+            let (v, k) = outer.find(e2, false).expect("head of edge");
+            outer.vertices[v][k] = Slot { edge: e1, tail: false };
+            let d = graph.tjs[e1] + 1;
+            reduction.mul(SignedSqrt(Rational::from((1, d * d))));
+            reduce(inner, reduction)?;
+            reduce(outer, reduction)
+        }
+        _ => {
+            // close each side with a new vertex
+            inner.vertices.push([
+                Slot { edge: boundary[0], tail: false },
+                Slot { edge: boundary[1], tail: false },
+                Slot { edge: boundary[2], tail: false },
+            ]);
+            outer.vertices.push([
+                Slot { edge: boundary[0], tail: true },
+                Slot { edge: boundary[1], tail: true },
+                Slot { edge: boundary[2], tail: true },
+            ]);
+            reduce(inner, reduction)?;
+            reduce(outer, reduction)
+        }
+    }
+}
+
+/// Irreducible graph with a known value.  Edge `e` goes from vertex
+/// `edges[e].0` to vertex `edges[e].1`.
+struct Template {
+    vertices: &'static [[usize; 3]],
+    edges: &'static [(usize, usize)],
+    value: fn(&[i32]) -> Option<Symbol>,
+}
+
+const THETA: Template = Template {
+    vertices: &[[0, 1, 2], [0, 1, 2]],
+    edges: &[(0, 1), (0, 1), (0, 1)],
+    value: theta_value,
+};
+
+const TETRAHEDRON: Template = Template {
+    vertices: &[[0, 1, 2], [0, 4, 5], [3, 1, 5], [3, 4, 2]],
+    edges: &[(1, 0), (2, 0), (3, 0), (2, 3), (3, 1), (1, 2)],
+    value: tetrahedron_value,
+};
+
+/// The complete bipartite graph `K_{3,3}` of the 9-j symbol.
+const NINE_J: Template = Template {
+    vertices: &[
+        [0, 1, 2], [3, 4, 5], [6, 7, 8],
+        [0, 3, 6], [1, 4, 7], [2, 5, 8],
+    ],
+    edges: &[
+        (0, 3), (0, 4), (0, 5),
+        (1, 3), (1, 4), (1, 5),
+        (2, 3), (2, 4), (2, 5),
+    ],
+    value: nine_j_value,
+};
+
+const CUBE: Template = Template {
+    vertices: &[
+        [0, 4, 8], [0, 5, 10], [1, 6, 8], [1, 7, 10],
+        [2, 4, 9], [2, 5, 11], [3, 6, 9], [3, 7, 11],
+    ],
+    edges: &[
+        (0, 1), (3, 2), (5, 4), (6, 7),
+        (0, 4), (5, 1), (6, 2), (3, 7),
+        (0, 2), (6, 4), (3, 1), (5, 7),
+    ],
+    value: cube_value,
+};
+
+fn theta_value(_: &[i32]) -> Option<Symbol> {
+    None
+}
+
+fn tetrahedron_value(tjs: &[i32]) -> Option<Symbol> {
+    Some(Symbol::Wigner6j(Wigner6j {
+        tj1: tjs[0],
+        tj2: tjs[1],
+        tj3: tjs[2],
+        tj4: tjs[3],
+        tj5: tjs[4],
+        tj6: tjs[5],
+    }))
+}
+
+fn nine_j_value(tjs: &[i32]) -> Option<Symbol> {
+    Some(Symbol::Wigner9j(Wigner9j {
+        tj1: tjs[0],
+        tj2: tjs[1],
+        tj3: tjs[2],
+        tj4: tjs[3],
+        tj5: tjs[4],
+        tj6: tjs[5],
+        tj7: tjs[6],
+        tj8: tjs[7],
+        tj9: tjs[8],
+    }))
+}
+
+fn cube_value(tjs: &[i32]) -> Option<Symbol> {
+    Some(Symbol::Wigner12jSecond(Wigner12jSecond {
+        tj1: tjs[0],
+        tj2: tjs[1],
+        tj3: tjs[2],
+        tj4: tjs[3],
+        tj5: tjs[4],
+        tj6: tjs[5],
+        tj7: tjs[6],
+        tj8: tjs[7],
+        tj9: tjs[8],
+        tj10: tjs[9],
+        tj11: tjs[10],
+        tj12: tjs[11],
+    }))
+}
+
+fn match_template(
+    graph: &Graph,
+    reduction: &mut Reduction,
+) -> Result<(), YutsisError>
+{
+    let template = match graph.vertices.len() {
+        2 => THETA,
+        4 => TETRAHEDRON,
+        6 => NINE_J,
+        8 => CUBE,
+        _ => return Err(YutsisError::Irreducible),
+    };
+    let n = template.vertices.len();
+    let mut perm: Vec<usize> = (0 .. n).collect();
+    loop {
+        if let Some((p, tjs)) = try_template(graph, &template, &perm) {
+            reduction.mul_phase(p);
+            reduction.symbols.extend((template.value)(&tjs));
+            return Ok(());
+        }
+        if !next_permutation(&mut perm) {
+            return Err(YutsisError::Irreducible);
+        }
+    }
+}
+
+/// Try to map template vertex `i` to graph vertex `perm[i]`, returning the
+/// phase needed to bring the graph into the form of the template.
+fn try_template(
+    graph: &Graph,
+    template: &Template,
+    perm: &[usize],
+) -> Option<(i32, Vec<i32>)>
+{
+    let mut graph = graph.clone();
+    let mut p = 1;
+    let mut edges = Vec::with_capacity(template.edges.len());
+    for &(t, h) in template.edges {
+        let (v, w) = (perm[t], perm[h]);
+        // parallel edges are only found in the theta graph, where any
+        // assignment works as long as each is used once
+        let e = graph.vertices[v].iter()
+            .map(|s| s.edge)
+            .find(|&e| {
+                !edges.contains(&e)
+                    && graph.vertices[w].iter().any(|s| s.edge == e)
+            })?;
+        if !graph.vertices[v].iter().any(|s| s.edge == e && s.tail) {
+            p *= graph.reverse(e);
+        }
+        edges.push(e);
+    }
+    for (i, tvertex) in template.vertices.iter().enumerate() {
+        let slots = graph.vertices[perm[i]];
+        let mut order = [0; 3];
+        for k in 0 .. 3 {
+            order[k] = slots.iter().position(|s| s.edge == edges[tvertex[k]])?;
+        }
+        p *= graph.permute(perm[i], order);
+    }
+    Some((p, edges.iter().map(|&e| graph.tjs[e]).collect()))
+}
+
+fn next_permutation(xs: &mut [usize]) -> bool {
+    let n = xs.len();
+    if n < 2 {
+        return false;
+    }
+    let mut i = n - 1;
+    while i > 0 && xs[i - 1] >= xs[i] {
+        i -= 1;
+    }
+    if i == 0 {
+        return false;
+    }
+    let mut j = n - 1;
+    while xs[j] <= xs[i - 1] {
+        j -= 1;
+    }
+    xs.swap(i - 1, j);
+    xs[i ..].reverse();
+    true
+}
+
+fn sum_projections(
+    graph: &Graph,
+    edges: &[usize],
+    i: usize,
+    tms: &mut [i32],
+    sum: &mut SignedSqrt,
+) {
+    if i == edges.len() {
+        let mut term = SignedSqrt::from(1);
+        for &e in edges {
+            term = term * phase((graph.tjs[e] - tms[e]) / 2);
+        }
+        for slots in &graph.vertices {
+            let tm = |s: Slot| if s.tail { tms[s.edge] } else { -tms[s.edge] };
+            term = term * Wigner3jm {
+                tj1: graph.tj(slots[0]),
+                tm1: tm(slots[0]),
+                tj2: graph.tj(slots[1]),
+                tm2: tm(slots[1]),
+                tj3: graph.tj(slots[2]),
+                tm3: tm(slots[2]),
+            }.value();
+            if term.sign() == Ordering::Equal {
+                return;
+            }
+        }
+        let s = ::std::mem::take(sum);
+        *sum = s.checked_add(term).expect("commensurate terms");
+        return;
+    }
+    let e = edges[i];
+    for tm in get_tms(graph.tjs[e]) {
+        tms[e] = tm;
+        // prune once all edges of a vertex are assigned
+        let ok = graph.vertices.iter().all(|slots| {
+            if slots.iter().any(|s| !edges[.. i + 1].contains(&s.edge)) {
+                return true;
+            }
+            slots.iter()
+                .map(|s| if s.tail { tms[s.edge] } else { -tms[s.edge] })
+                .sum::<i32>() == 0
+        });
+        if ok {
+            sum_projections(graph, edges, i + 1, tms, sum);
+        }
+    }
+}
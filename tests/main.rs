@@ -4,12 +4,15 @@ extern crate rug;
 extern crate wigner_symbols;
 
 use std::{cmp, fmt, hash};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
+use std::sync::atomic::{self, AtomicUsize};
 use rug::Rational;
 use wigner_symbols::*;
 use wigner_symbols::internal::*;
+use wigner_symbols::racah::{Expression, Factor, Linear, RacahError};
 use wigner_symbols::regge::*;
+use wigner_symbols::yutsis::{Edge, Symbol, Vertex, YutsisError, YutsisGraph};
 
 const CG_HASHES: &[(i32, &str)] = &[
     (5, "e74c501299b456a6cb29e4f5714e9061"), // 681
@@ -39,8 +42,8 @@ const W9J_HASHES: &[(i32, &str)] = &[
 
 const W12J_SECOND_HASHES: &[(i32, &str)] = &[
     (0, "1133f539027171d60d87a58f56abf094"),
-    (2, "f6c1c20b5613ba1c128783dd4d3e8ff5"),
-    (4, "589b4b938439ca45c9ec24d516f3a4f0"),
+    (2, "b84021483312e28058203a82dc4e6957"),
+    (4, "33d91cc53f5b8be7df0cfa88c51bd975"),
 ];
 
 fn lookup<'a, K: Eq, V>(table: &'a [(K, V)], key: &K) -> Option<&'a V> {
@@ -228,6 +231,12 @@ fn test_wigner_9j() {
 fn test_signed_sqrt_rational() {
     assert_eq!(f64::from(SignedSqrt::default()), 0.0);
     assert_eq!(f64::from(SignedSqrt::new(10.into(), (1, 4).into())), 5.0);
+    let sqrt2 = SignedSqrt::new(1.into(), 2.into());
+    assert_eq!(sqrt2.clone().checked_add(SignedSqrt::new((-3).into(), 8.into())),
+               Some(SignedSqrt::new((-5).into(), 2.into())));
+    assert_eq!(sqrt2.clone().checked_add(SignedSqrt::new((-1).into(), 2.into())),
+               Some(SignedSqrt::default()));
+    assert_eq!(sqrt2.checked_add(SignedSqrt::from(1)), None);
 }
 
 #[test]
@@ -289,3 +298,1371 @@ fn test_wigner_12j_second() {
     assert_eq!(&format!("{:x}", f.compute()),
                *lookup(W12J_SECOND_HASHES, &tj_max).expect("hash not available"));
 }
+
+/// Value of a closed network of 3-jm symbols by explicit summation,
+///
+/// ```text
+/// Σ[m] ∏[edges] (−1)^(j − m) ∏[vertices] (j1 j2 j3; μ1 μ2 μ3)
+/// ```
+///
+/// where `μ = +m` at the tail of the edge and `μ = −m` at its head.
+fn sum_network(
+    tjs: &[i32],
+    edges: &[(usize, usize)],
+    vertices: &[[usize; 3]],
+) -> SignedSqrt
+{
+    fn go(
+        tjs: &[i32],
+        edges: &[(usize, usize)],
+        vertices: &[[usize; 3]],
+        tms: &mut Vec<i32>,
+        sum: &mut SignedSqrt,
+    ) {
+        let mu = |tms: &[i32], v: usize, e: usize| {
+            if edges[e].0 == v { tms[e] } else { -tms[e] }
+        };
+        let i = tms.len();
+        if i == edges.len() {
+            let mut term = SignedSqrt::from(1);
+            for (e, &tm) in tms.iter().enumerate() {
+                term = term * phase((tjs[e] - tm) / 2);
+            }
+            for (v, slots) in vertices.iter().enumerate() {
+                term = term * Wigner3jm {
+                    tj1: tjs[slots[0]],
+                    tm1: mu(tms, v, slots[0]),
+                    tj2: tjs[slots[1]],
+                    tm2: mu(tms, v, slots[1]),
+                    tj3: tjs[slots[2]],
+                    tm3: mu(tms, v, slots[2]),
+                }.value();
+            }
+            let s = std::mem::take(sum);
+            *sum = s.checked_add(term).expect("commensurate terms");
+            return;
+        }
+        for tm in get_tms(tjs[i]) {
+            tms.push(tm);
+            let ok = vertices.iter().enumerate().all(|(v, slots)| {
+                slots.iter().any(|&e| e > i)
+                    || slots.iter().map(|&e| mu(tms, v, e)).sum::<i32>() == 0
+            });
+            if ok {
+                go(tjs, edges, vertices, tms, sum);
+            }
+            tms.pop();
+        }
+    }
+    let mut sum = SignedSqrt::default();
+    go(tjs, edges, vertices, &mut Vec::new(), &mut sum);
+    sum
+}
+
+#[test]
+fn test_wigner_12j_second_by_summation() {
+    // the cube, with the 6-j factors of the 12-j symbol as its faces
+    let vertices = [[0, 4, 8], [0, 5, 10], [1, 6, 8], [1, 7, 10],
+                    [2, 4, 9], [2, 5, 11], [3, 6, 9], [3, 7, 11]];
+    let edges = [(0, 1), (3, 2), (5, 4), (6, 7),
+                 (0, 4), (5, 1), (6, 2), (3, 7),
+                 (0, 2), (6, 4), (3, 1), (5, 7)];
+    let mut odd_phase = 0;
+    get_12tjs_second(2, &mut |w12j| {
+        let Wigner12jSecond {
+            tj1, tj2, tj3, tj4, tj5, tj6, tj7, tj8, tj9, tj10, tj11, tj12,
+        } = w12j;
+        let tjs = [tj1, tj2, tj3, tj4, tj5, tj6, tj7, tj8, tj9, tj10, tj11, tj12];
+        let value = w12j.value();
+        if value != SignedSqrt::default() && (tj5 - tj6 - tj7 + tj8) % 4 != 0 {
+            odd_phase += 1;
+        }
+        assert_eq!(value, sum_network(&tjs, &edges, &vertices), "{:?}", w12j);
+    });
+    assert!(odd_phase > 0);
+}
+
+#[test]
+fn test_regge_overflow() {
+    assert_eq!(CanonicalRegge3jm::<u8>::checked_len(254), Ok(CanonicalRegge3jm::len(254)));
+    assert_eq!(CanonicalRegge3jm::<u8>::checked_len(255), Err(ReggeOverflow::Key));
+    assert_eq!(CanonicalRegge6j::<u8>::checked_len(255), Err(ReggeOverflow::Key));
+    assert!(CanonicalRegge3jm::<u16>::checked_len(255).is_ok());
+    assert!(CanonicalRegge6j::<u16>::checked_len(255).is_ok());
+    assert_eq!(CanonicalRegge6j::<u32>::checked_len(i32::MAX),
+               Err(ReggeOverflow::Key));
+    assert_eq!(CanonicalRegge6j::<u32>::checked_len(1 << 20),
+               Err(ReggeOverflow::Index));
+
+    let w3jm = Wigner3jm { tj1: 300, tm1: 2, tj2: 300, tm2: 0, tj3: 600, tm3: -2 };
+    assert_eq!(Regge3jm::from(w3jm).checked_canonicalize::<u8>(),
+               Err(ReggeOverflow::Key));
+    let (regge, _) = Regge3jm::from(w3jm).checked_canonicalize::<u16>().unwrap();
+    assert_eq!(Some(regge.index()), regge.checked_index());
+    assert!(regge.index() < CanonicalRegge3jm::<u16>::checked_len(600).unwrap());
+
+    let w6j = Wigner6j { tj1: 300, tj2: 300, tj3: 600, tj4: 300, tj5: 300, tj6: 600 };
+    assert_eq!(CanonicalRegge6j::<u8>::checked_from(w6j), Err(ReggeOverflow::Key));
+    let regge = CanonicalRegge6j::<u16>::checked_from(w6j).unwrap();
+    assert_eq!(Some(regge.index()), regge.checked_index());
+
+    // distinct symbols must not alias once the keys are wide enough
+    let a = CanonicalRegge6j::<u16>::checked_from(
+        Wigner6j { tj1: 2, tj2: 2, tj3: 2, tj4: 2, tj5: 2, tj6: 2 }).unwrap();
+    let b = CanonicalRegge6j::<u16>::checked_from(
+        Wigner6j { tj1: 258, tj2: 258, tj3: 2, tj4: 258, tj5: 258, tj6: 2 }).unwrap();
+    assert!(a != b && a.index() != b.index());
+}
+
+#[test]
+#[should_panic(expected = "Regge index overflows usize")]
+fn test_regge_index_overflow() {
+    let m = u32::MAX;
+    CanonicalRegge6j::<u32> { e: m, l: m, x: m, t: m, b: m, s: m }.index();
+}
+
+#[test]
+fn test_canonical_3jm() {
+    let tj_max = 8;
+    let mut classes = HashMap::new();
+    get_3tjms(tj_max, &mut |w3jm| {
+        let value = w3jm.value();
+        let (canon, phase) = w3jm.canonical();
+        assert_eq!(value, phase * canon.value());
+        let (canon2, phase2) = canon.canonical();
+        assert_eq!(canon2, canon);
+        // a nontrivial phase is possible only if the symbol is odd under some
+        // symmetry, in which case it vanishes
+        assert!(phase2 == 1 || value.sign() == cmp::Ordering::Equal);
+        let (regge, regge_phase) = Regge3jm::from(w3jm).canonicalize();
+        assert_eq!(phase, regge_phase);
+        assert_eq!(Wigner3jm::from(regge), canon);
+        let first = *classes.entry(canon).or_insert(w3jm);
+        assert_eq!(first.equivalent(w3jm), Some(first.canonical().1 * phase));
+
+        let cg = ClebschGordan::from(w3jm);
+        let (cg_canon, c) = cg.canonical();
+        assert_eq!(cg.value(), c * cg_canon.value());
+        let cg_first = ClebschGordan::from(first);
+        let c = cg.equivalent(cg_first).unwrap();
+        assert_eq!(cg.value(), c * cg_first.value());
+    });
+    let w1 = Wigner3jm { tj1: 2, tm1: 0, tj2: 2, tm2: 0, tj3: 2, tm3: 0 };
+    let w2 = Wigner3jm { tj1: 2, tm1: 2, tj2: 2, tm2: -2, tj3: 2, tm3: 0 };
+    assert_eq!(w1.equivalent(w2), None);
+}
+
+#[test]
+fn test_regge_from_index() {
+    let tj_max = 12;
+    let mut expected = HashSet::new();
+    get_3tjms(tj_max, &mut |w3jm| {
+        expected.insert(Regge3jm::from(w3jm).canonicalize().0);
+    });
+    let mut found = HashSet::new();
+    for regge in CanonicalRegge3jm::keys(tj_max) {
+        assert_eq!(CanonicalRegge3jm::from_index(regge.index()), regge);
+        let (canon, phase) = Wigner3jm::from(regge).canonical();
+        assert_eq!(Wigner3jm::from(regge), canon);
+        assert!(phase == 1 || canon.value() == SignedSqrt::default());
+        found.insert(regge);
+    }
+    assert!(expected.is_subset(&found));
+    for i in 0 .. CanonicalRegge3jm::len(tj_max) {
+        let regge = CanonicalRegge3jm::from_index(i);
+        assert_eq!(regge.index(), i);
+        assert_eq!(regge.is_canonical(), found.contains(&regge));
+    }
+
+    let n = CanonicalRegge6j::len(tj_max);
+    assert_eq!(CanonicalRegge6j::keys(tj_max).len(), n);
+    for (i, regge) in CanonicalRegge6j::keys(tj_max).enumerate() {
+        assert_eq!(CanonicalRegge6j::from_index(i), regge);
+        assert_eq!(regge.index(), i);
+        assert_eq!(CanonicalRegge6j::from(Wigner6j::from(regge)), regge);
+    }
+
+    let big = CanonicalRegge6j::<u32> { e: 1000, l: 900, x: 800, t: 7, b: 6, s: 5 };
+    assert_eq!(CanonicalRegge6j::<u32>::checked_from_index(big.index()), Ok(big));
+    assert_eq!(CanonicalRegge6j::<u8>::checked_from_index(big.index()),
+               Err(ReggeOverflow::Key));
+}
+
+#[test]
+fn test_canonical_enumeration() {
+    let tj_max = 10;
+    let mut expected = HashSet::new();
+    get_3tjms(tj_max, &mut |w3jm| {
+        expected.insert(Regge3jm::from(w3jm).canonicalize().0);
+    });
+    let mut found = HashSet::new();
+    get_canonical_3tjms(tj_max, &mut |w3jm| {
+        assert!(wigner_3jm_selection_rules(w3jm));
+        assert!(w3jm.tj1.max(w3jm.tj2).max(w3jm.tj3) <= tj_max);
+        let (regge, _) = Regge3jm::from(w3jm).canonicalize();
+        assert_eq!(w3jm.value(), Wigner3jm::from(regge).value());
+        assert!(found.insert(regge));
+    });
+    assert_eq!(found, expected);
+
+    let mut expected = HashSet::new();
+    get_6tjs(tj_max, &mut |w6j| {
+        expected.insert(CanonicalRegge6j::from(w6j));
+    });
+    let mut found = HashSet::new();
+    get_canonical_6tjs(tj_max, &mut |w6j| {
+        assert!(*[w6j.tj1, w6j.tj2, w6j.tj3, w6j.tj4, w6j.tj5, w6j.tj6]
+                .iter().max().unwrap() <= tj_max);
+        assert!(found.insert(CanonicalRegge6j::from(w6j)));
+    });
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_parallel_tables() {
+    let tj_max = 10;
+    let progress = AtomicUsize::new(0);
+    let report = |done: usize, total: usize| {
+        assert!(done <= total);
+        progress.fetch_max(done, atomic::Ordering::SeqCst);
+    };
+    let table = table::build_6j_table(tj_max, 1, &report);
+    assert_eq!(progress.load(atomic::Ordering::SeqCst), table.len());
+    assert_eq!(table.len(), CanonicalRegge6j::len(tj_max));
+    assert_eq!(table::build_6j_table(tj_max, 4, &|_, _| ()), table);
+    get_6tjs(tj_max, &mut |w6j| {
+        assert_eq!(table[CanonicalRegge6j::from(w6j).index()], w6j.value());
+    });
+
+    let table = table::build_3jm_table(tj_max, 1, &|_, _| ());
+    assert_eq!(table.len(), CanonicalRegge3jm::len(tj_max));
+    assert_eq!(table::build_3jm_table(tj_max, 3, &|_, _| ()), table);
+    get_3tjms(tj_max, &mut |w3jm| {
+        let (regge, phase) = Regge3jm::from(w3jm).canonicalize();
+        assert_eq!(table[regge.index()], phase * w3jm.value());
+    });
+}
+
+#[test]
+fn test_recoupling() {
+    use recoupling::{recoupling, CouplingTree};
+    let leaf = CouplingTree::leaf;
+    let couple = CouplingTree::couple;
+    let (ta, tb, tc, td) = (1, 2, 3, 2);
+    for tj in 0 .. 9 {
+        // ⟨((a b)e c)J | (a (b c)f)J⟩
+        for te in get_triangular_tjs(100, ta, tb) {
+            for tf in get_triangular_tjs(100, tb, tc) {
+                let x = couple(couple(leaf(0, ta), leaf(1, tb), te), leaf(2, tc), tj);
+                let y = couple(leaf(0, ta), couple(leaf(1, tb), leaf(2, tc), tf), tj);
+                let expected = phase((ta + tb + tc + tj) / 2)
+                    * SignedSqrt(Rational::from((te + 1) * (tf + 1)))
+                    * Wigner6j { tj1: ta, tj2: tb, tj3: te, tj4: tc, tj5: tj, tj6: tf }
+                        .value();
+                assert_eq!(recoupling(&x, &y), expected);
+                assert_eq!(recoupling(&y, &x), expected);
+            }
+        }
+    }
+
+    // ⟨((a b)e (c d)f)J | ((a c)g (b d)h)J⟩ is a 9-j symbol
+    let tj = 4;
+    let mut count = 0;
+    for te in get_triangular_tjs(100, ta, tb) {
+    for tf in get_triangular_tjs(100, tc, td) {
+    for tg in get_triangular_tjs(100, ta, tc) {
+    for th in get_triangular_tjs(100, tb, td) {
+        let x = couple(couple(leaf(0, ta), leaf(1, tb), te),
+                       couple(leaf(2, tc), leaf(3, td), tf), tj);
+        let y = couple(couple(leaf(0, ta), leaf(2, tc), tg),
+                       couple(leaf(1, tb), leaf(3, td), th), tj);
+        let expected = SignedSqrt(Rational::from((te + 1) * (tf + 1) * (tg + 1) * (th + 1)))
+            * Wigner9j {
+                tj1: ta, tj2: tb, tj3: te,
+                tj4: tc, tj5: td, tj6: tf,
+                tj7: tg, tj8: th, tj9: tj,
+            }.value();
+        assert_eq!(recoupling(&x, &y), expected);
+        count += (expected != SignedSqrt::default()) as i32;
+    }
+    }
+    }
+    }
+    assert!(count > 0);
+
+    // orthogonality over the intermediate angular momenta of a basis
+    let trees = |tj: i32| {
+        let mut trees = Vec::new();
+        for tk in get_triangular_tjs(100, tc, ta) {
+            for tl in get_triangular_tjs(100, tk, td) {
+                trees.push(couple(
+                    couple(couple(leaf(2, tc), leaf(0, ta), tk), leaf(3, td), tl),
+                    leaf(1, tb), tj));
+            }
+        }
+        trees
+    };
+    let x = couple(leaf(0, ta), couple(leaf(1, tb),
+                   couple(leaf(2, tc), leaf(3, td), 3), 3), 4);
+    let y = couple(couple(leaf(3, td), leaf(1, tb), 2),
+                   couple(leaf(0, ta), leaf(2, tc), 4), 4);
+    assert_eq!(recoupling(&x, &x), SignedSqrt::from(1));
+    let mut norm = Rational::default();
+    let mut overlap = SignedSqrt::default();
+    for z in trees(4) {
+        let xz = recoupling(&x, &z);
+        norm += xz.clone().sq();
+        overlap = overlap.checked_add(xz * recoupling(&z, &y)).unwrap();
+    }
+    assert_eq!(norm, 1);
+    assert_eq!(overlap, recoupling(&x, &y));
+    assert!(overlap != SignedSqrt::default());
+}
+
+#[test]
+fn test_yutsis() {
+    let graph = |vertices: &[[usize; 3]], edges: &[(usize, usize)], tjs: &[i32]| {
+        YutsisGraph {
+            edges: edges.iter().zip(tjs).map(|(&(tail, head), &tj)| {
+                Edge { tj, tail, head }
+            }).collect(),
+            vertices: vertices.iter().enumerate().map(|(i, &edges)| {
+                Vertex { edges, sign: if i % 3 == 1 { -1 } else { 1 } }
+            }).collect(),
+        }
+    };
+    let check = |g: &YutsisGraph| {
+        let value = g.value().unwrap();
+        assert_eq!(value, g.value_by_summation().unwrap());
+        value
+    };
+
+    // tetrahedron
+    let tetrahedron = [[0, 1, 2], [0, 4, 5], [3, 1, 5], [3, 4, 2]];
+    let tetrahedron_edges = [(1, 0), (2, 0), (3, 0), (2, 3), (3, 1), (1, 2)];
+    for &tjs in &[[1, 1, 2, 1, 1, 2], [2, 2, 2, 2, 2, 2], [2, 1, 1, 2, 3, 3],
+                  [1, 1, 0, 1, 1, 2]] {
+        let g = graph(&tetrahedron, &tetrahedron_edges, &tjs);
+        let r = g.reduce().unwrap();
+        assert!(r.symbols.len() <= 1);
+        assert!(r.symbols.iter().all(|s| matches!(*s, Symbol::Wigner6j(_))));
+        check(&g);
+    }
+
+    // two triangles joined by three edges reduce to two 6-j symbols
+    let prism = [[0, 2, 6], [0, 1, 7], [1, 2, 8], [3, 5, 6], [3, 4, 7], [4, 5, 8]];
+    let prism_edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3),
+                       (0, 3), (4, 1), (2, 5)];
+    let g = graph(&prism, &prism_edges, &[1, 2, 1, 1, 2, 1, 2, 1, 1]);
+    assert_eq!(g.reduce().unwrap().symbols.len(), 2);
+    assert!(check(&g) != SignedSqrt::default());
+
+    // 9-j symbol
+    let nine = [[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8]];
+    let nine_edges = [(0, 3), (0, 4), (0, 5), (1, 3), (1, 4), (1, 5),
+                      (2, 3), (2, 4), (2, 5)];
+    let g = graph(&nine, &nine_edges, &[2, 1, 1, 1, 2, 1, 1, 1, 2]);
+    match g.reduce().unwrap().symbols[..] {
+        [Symbol::Wigner9j(_)] => {}
+        ref s => panic!("unexpected symbols: {:?}", s),
+    }
+    assert!(check(&g) != SignedSqrt::default());
+
+    // 12-j symbol of the second kind
+    let cube = [[0, 4, 8], [0, 5, 10], [1, 6, 8], [1, 7, 10],
+                [2, 4, 9], [2, 5, 11], [3, 6, 9], [3, 7, 11]];
+    let cube_edges = [(0, 1), (3, 2), (5, 4), (6, 7), (0, 4), (5, 1),
+                      (6, 2), (3, 7), (0, 2), (6, 4), (3, 1), (5, 7)];
+    let g = graph(&cube, &cube_edges, &[1, 1, 1, 1, 1, 2, 1, 2, 2, 2, 1, 1]);
+    match g.reduce().unwrap().symbols[..] {
+        [Symbol::Wigner12jSecond(_)] => {}
+        ref s => panic!("unexpected symbols: {:?}", s),
+    }
+    assert!(check(&g) != SignedSqrt::default());
+
+    // loops, bubbles, and disconnected pieces
+    let g = graph(&[[0, 0, 1], [2, 1, 2]], &[(0, 0), (0, 1), (1, 1)], &[2, 0, 3]);
+    assert_eq!(check(&g), SignedSqrt(12.into()));
+    let g = graph(&[[0, 1, 2], [0, 1, 3], [4, 5, 2], [4, 5, 3]],
+                  &[(0, 1), (0, 1), (0, 2), (1, 3), (2, 3), (2, 3)],
+                  &[1, 1, 2, 2, 1, 1]);
+    assert_eq!(check(&g), SignedSqrt(Rational::from((1, 9))));
+
+    let mut g = graph(&tetrahedron, &tetrahedron_edges, &[1, 1, 2, 1, 1, 2]);
+    g.edges[0].head = 1;
+    assert_eq!(g.reduce(), Err(YutsisError::Malformed));
+}
+
+#[test]
+fn test_racah_expression() {
+    let v = Linear::var;
+
+    // 9-j symbol as a sum over x (variable 9) of three 6-j symbols
+    let nine = Expression {
+        sums: vec![9],
+        factors: vec![
+            Factor::Phase(v(9) * 2),
+            Factor::Dimension(v(9), 2),
+            Factor::Wigner6j([v(0), v(3), v(6), v(7), v(8), v(9)]),
+            Factor::Wigner6j([v(1), v(4), v(7), v(3), v(9), v(5)]),
+            Factor::Wigner6j([v(2), v(5), v(8), v(9), v(0), v(1)]),
+        ],
+    };
+    let mut count = 0;
+    get_9tjs(4, &mut |w| {
+        let values = [w.tj1, w.tj2, w.tj3, w.tj4, w.tj5, w.tj6,
+                      w.tj7, w.tj8, w.tj9];
+        let values: Vec<_> = values.iter().cloned().enumerate().collect();
+        assert_eq!(nine.evaluate(&values), Ok(w.value()));
+        count += 1;
+    });
+    assert!(count > 0);
+
+    // Σ[m1 m2] (j1 j2 j3; m1 m2 m3) (j1 j2 j3'; m1 m2 m3')
+    //   = δ(j3, j3') δ(m3, m3') / (2 j3 + 1)
+    let orthogonality = Expression {
+        sums: vec![3, 4],
+        factors: vec![
+            Factor::Wigner3jm([v(0), v(3), v(1), v(4), v(2), v(5)]),
+            Factor::Wigner3jm([v(0), v(3), v(1), v(4), v(6), -v(3) - v(4)]),
+        ],
+    };
+    for &(tj1, tj2, tj3, tm3, tj3p) in &[(1, 2, 3, -1, 3), (2, 2, 2, 0, 2),
+                                         (2, 2, 2, 0, 4), (1, 1, 0, 0, 2)] {
+        let expected = if tj3 == tj3p {
+            SignedSqrt(Rational::from((1, (tj3 + 1) * (tj3 + 1))))
+        } else {
+            SignedSqrt::default()
+        };
+        let values = [(0, tj1), (1, tj2), (2, tj3), (5, tm3), (6, tj3p)];
+        assert_eq!(orthogonality.evaluate(&values), Ok(expected));
+    }
+
+    assert_eq!(orthogonality.evaluate(&[(0, 1), (1, 1), (2, 2), (6, 2)]),
+               Err(RacahError::Unbound(5)));
+    let unbounded = Expression {
+        sums: vec![0],
+        factors: vec![Factor::Dimension(v(0), 2)],
+    };
+    assert_eq!(unbounded.evaluate(&[]), Err(RacahError::Unbounded(0)));
+    let odd = Expression {
+        sums: vec![],
+        factors: vec![Factor::Phase(v(0) + Linear::from(1))],
+    };
+    assert_eq!(odd.evaluate(&[(0, 2)]), Err(RacahError::OddPhase));
+}
+
+#[test]
+fn test_identities() {
+    use wigner_symbols::identities::*;
+    if let Err(e) = check_all(6) {
+        panic!("{}", e);
+    }
+    assert_eq!(check_3jm_orthogonality(8), Ok(()));
+    assert_eq!(check_6j_orthogonality(8), Ok(()));
+    assert_eq!(check_racah_sum_rule(8), Ok(()));
+}
+
+#[test]
+fn test_wigner_eckart() {
+    use wigner_symbols::tensor::{Convention, WignerEckart};
+    for tj in 0 .. 6 {
+        // ⟨j‖J‖j⟩ = √(j (j + 1) (2 j + 1)) in the Edmonds convention
+        let edmonds = WignerEckart {
+            tj1: tj,
+            tk: 2,
+            tj2: tj,
+            convention: Convention::Edmonds,
+        };
+        let brink = WignerEckart { convention: Convention::BrinkSatchler, ..edmonds };
+        let reduced = SignedSqrt(Rational::from((tj * (tj + 2) * (tj + 1), 4)));
+        let reduced_brink = edmonds.convert(reduced.clone(), Convention::BrinkSatchler);
+        assert_eq!(brink.convert(reduced_brink.clone(), Convention::Edmonds),
+                   reduced);
+
+        let j0 = edmonds.matrix(reduced.clone(), 0);
+        let jp = edmonds.matrix(reduced.clone(), 2);
+        assert_eq!(j0, brink.matrix(reduced_brink.clone(), 0));
+        assert_eq!(jp, brink.matrix(reduced_brink, 2));
+        for (i, tm1) in get_tms(tj).enumerate() {
+            for (k, tm2) in get_tms(tj).enumerate() {
+                let expected = if tm1 == tm2 {
+                    SignedSqrt(Rational::from((tm1 * tm1.abs(), 4)))
+                } else {
+                    SignedSqrt::default()
+                };
+                assert_eq!(j0[i][k], expected);
+                // J_{+1} = −J_+ / √2
+                let expected = if tm1 == tm2 + 2 {
+                    SignedSqrt(Rational::from((-(tj - tm2) * (tj + tm2 + 2), 8)))
+                } else {
+                    SignedSqrt::default()
+                };
+                assert_eq!(jp[i][k], expected);
+                if tm1 == tm2 && tm1 != 0 {
+                    assert_eq!(edmonds.reduced(j0[i][k].clone(), tm1, 0, tm2),
+                               Some(reduced.clone()));
+                }
+            }
+        }
+        assert_eq!(edmonds.reduced(SignedSqrt::from(1), tj, 2, tj), None);
+    }
+}
+
+#[test]
+fn test_composite_reduced() {
+    use wigner_symbols::tensor::{Composite, Convention, CoupledState};
+    // ⟨j‖J‖j⟩ = √(j (j + 1) (2 j + 1)) in the Edmonds convention
+    let jj = |tj: i32| SignedSqrt(Rational::from((tj * (tj + 2) * (tj + 1), 4)));
+    let (tj1, tj2) = (2, 3);
+    for tj in get_triangular_tjs(100, tj1, tj2) {
+        let state = CoupledState { tj1, tj2, tj };
+        for &convention in &[Convention::Edmonds, Convention::BrinkSatchler] {
+            let c = Composite { convention, bra: state, ket: state };
+            let (j1, j2) = if convention == Convention::Edmonds {
+                (jj(tj1), jj(tj2))
+            } else {
+                (SignedSqrt(Rational::from((tj1 * (tj1 + 2), 4))),
+                 SignedSqrt(Rational::from((tj2 * (tj2 + 2), 4))))
+            };
+            // J = J1 + J2
+            let j = f64::from(c.first(2, j1.clone()))
+                + f64::from(c.second(2, j2.clone()));
+            let expected = if convention == Convention::Edmonds {
+                f64::from(jj(tj))
+            } else {
+                f64::from(SignedSqrt(Rational::from((tj * (tj + 2), 4))))
+            };
+            assert!((j - expected).abs() < 1e-12, "{} {}", j, expected);
+            let jf = c.first(2, f64::from(j1.clone()))
+                + c.second(2, f64::from(j2.clone()));
+            assert!((jf - expected).abs() < 1e-12);
+
+            // J1 · J2 = (J² − J1² − J2²) / 2
+            let dot = c.scalar_product(2, j1.clone(), j2.clone());
+            let expected = Rational::from((
+                tj * (tj + 2) - tj1 * (tj1 + 2) - tj2 * (tj2 + 2),
+                8,
+            ));
+            assert_eq!(dot.signed_sq(), expected.clone() * expected.clone().abs());
+
+            // [J1 ⊗ J2]^0 = −J1 · J2 / √3
+            let scalar = c.tensor_product(2, 2, 0, j1, j2);
+            let value = if convention == Convention::Edmonds {
+                f64::from(scalar) / ((tj + 1) as f64).sqrt()
+            } else {
+                f64::from(scalar)
+            };
+            let expected = -expected.to_f64() / 3f64.sqrt();
+            assert!((value - expected).abs() < 1e-12, "{} {}", value, expected);
+        }
+    }
+}
+
+#[test]
+fn test_standard_operators() {
+    use std::f64::consts::PI;
+    use wigner_symbols::tensor::*;
+    for tl in (0 .. 10).map(|l| 2 * l) {
+        // C^0 is the identity
+        assert_eq!(racah_tensor(tl, 0, tl), SignedSqrt((tl + 1).into()));
+        // ⟨l‖C^1‖l − 1⟩ = √l and ⟨l‖C^1‖l + 1⟩ = −√(l + 1)
+        if tl > 0 {
+            assert_eq!(racah_tensor(tl, 2, tl - 2), SignedSqrt((tl / 2).into()));
+        }
+        assert_eq!(racah_tensor(tl, 2, tl + 2),
+                   SignedSqrt((-(tl / 2 + 1)).into()));
+        // parity
+        assert_eq!(racah_tensor(tl, 2, tl), SignedSqrt::default());
+        assert_eq!(angular_momentum(tl, tl),
+                   WignerEckart {
+                       tj1: tl,
+                       tk: 2,
+                       tj2: tl,
+                       convention: Convention::Edmonds,
+                   }.reduced(SignedSqrt(Rational::from((tl * tl, 4))), tl, 0, tl)
+                   .unwrap_or_default());
+    }
+    assert_eq!(angular_momentum(1, 1), SignedSqrt(Rational::from((3, 2))));
+    assert_eq!(angular_momentum(1, 3), SignedSqrt::default());
+    assert!((spherical_harmonic_f64(0, 0, 0) - 1.0 / (4.0 * PI).sqrt()).abs()
+            < 1e-15);
+    assert_eq!(spherical_harmonic(2, 2, 0), SignedSqrt(3.into()));
+}
+
+#[test]
+fn test_slater() {
+    use wigner_symbols::slater::*;
+    let r = |n, d| SignedSqrt(Rational::from((n, d)));
+    // 1s 2p
+    for (ts, g) in [(0, r(1, 9)), (2, r(-1, 9))] {
+        let term = TwoElectronLS { tl1: 0, tl2: 2, tl: 2, ts, equivalent: false };
+        assert_eq!(term.direct(0), 1.into());
+        assert_eq!(term.exchange(2), g);
+    }
+    // p²
+    let p2 = [(2, 2, r(-1, 25)), (4, 0, r(1, 625)), (0, 0, r(4, 25))];
+    let shell: Vec<_> = p2.iter().map(|&(tl, ts, _)| ShellTerm {
+        tl,
+        ts,
+        parents: vec![Parentage { parent: 0, tl: 2, ts: 1, cfp: 1.into() }],
+    }).collect();
+    for (i, &(tl, ts, ref f)) in p2.iter().enumerate() {
+        let term = TwoElectronLS { tl1: 2, tl2: 2, tl, ts, equivalent: true };
+        assert_eq!(term.direct(0), 1.into());
+        assert_eq!(&term.direct(4), f);
+        assert_eq!(term.exchange(4), SignedSqrt::default());
+        assert_eq!(shell_direct(2, 2, &shell, i, 4).as_ref(), Some(f));
+    }
+    // p³ ²P and ²D, with ⁴S
+    let parent = |parent, tl, ts, n, d| Parentage { parent, tl, ts, cfp: r(n, d) };
+    let doublets = [
+        ShellTerm { tl: 2, ts: 1, parents: vec![
+            parent(0, 2, 2, -1, 2), parent(1, 4, 0, -5, 18), parent(2, 0, 0, 2, 9),
+        ] },
+        ShellTerm { tl: 4, ts: 1, parents: vec![
+            parent(0, 2, 2, -1, 2), parent(1, 4, 0, 1, 2),
+        ] },
+    ];
+    assert_eq!(shell_direct(2, 3, &doublets, 0, 4), Some(0.into()));
+    assert_eq!(shell_direct(2, 3, &doublets, 1, 4), Some(r(-36, 625)));
+    let quartet = [ShellTerm { tl: 0, ts: 3, parents: vec![parent(0, 2, 2, 1, 1)] }];
+    assert_eq!(shell_direct(2, 3, &quartet, 0, 4), Some(r(-9, 25)));
+    // jj coupling agrees with LS coupling transformed by 9-j symbols
+    let (tl1, tl2) = (2, 4);
+    for tk in 0 .. 8 {
+        for &tj1 in &[tl1 - 1, tl1 + 1] {
+            for &tj2 in &[tl2 - 1, tl2 + 1] {
+                for tj in get_triangular_tjs(tj1 + tj2, tj1, tj2) {
+                    let jj = TwoElectronJJ {
+                        tl1, tj1, tl2, tj2, tj, equivalent: false,
+                    };
+                    let (mut f, mut g) = (0.0, 0.0);
+                    for tl in get_triangular_tjs(tl1 + tl2, tl1, tl2) {
+                        for &ts in &[0, 2] {
+                            let t = f64::from(Wigner9j {
+                                tj1: tl1, tj2: 1, tj3: tj1,
+                                tj4: tl2, tj5: 1, tj6: tj2,
+                                tj7: tl, tj8: ts, tj9: tj,
+                            }.value()).powi(2) * f64::from(
+                                (tj1 + 1) * (tj2 + 1) * (tl + 1) * (ts + 1));
+                            let ls = TwoElectronLS {
+                                tl1, tl2, tl, ts, equivalent: false,
+                            };
+                            f += t * f64::from(ls.direct(tk));
+                            g += t * f64::from(ls.exchange(tk));
+                        }
+                    }
+                    assert!((f64::from(jj.direct(tk)) - f).abs() < 1e-12);
+                    assert!((f64::from(jj.exchange(tk)) - g).abs() < 1e-12);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_moshinsky() {
+    use wigner_symbols::identities::check_moshinsky_orthogonality;
+    use wigner_symbols::moshinsky::{Brackets, MoshinskyBracket};
+    let half = SignedSqrt(Rational::from((1, 2)));
+    let b = |n1, tl1, n2, tl2, nr, tlr, nc, tlc, tl| MoshinskyBracket {
+        n1, tl1, n2, tl2, nr, tlr, nc, tlc, tl,
+    };
+    assert_eq!(b(0, 0, 0, 0, 0, 0, 0, 0, 0).value(), Ok(1.into()));
+    assert_eq!(b(0, 2, 0, 0, 0, 2, 0, 0, 2).value(), Ok(half.clone()));
+    assert_eq!(b(0, 0, 0, 2, 0, 2, 0, 0, 2).value(), Ok(-1 * half.clone()));
+    assert_eq!(b(1, 0, 0, 0, 0, 2, 0, 2, 0).value(), Ok(half));
+    assert_eq!(b(0, 2, 0, 2, 0, 2, 0, 2, 0).value(), Ok(SignedSqrt::default()));
+    check_moshinsky_orthogonality(8).unwrap();
+    // exchanging the particles reverses the relative coordinate
+    for &(tl1, tl2, tl, tlr, tlc) in &[(2, 4, 4, 2, 4), (4, 2, 2, 0, 6), (2, 2, 2, 4, 0)] {
+        let x = b(1, tl1, 0, tl2, 1, tlr, 0, tlc, tl);
+        let y = b(0, tl2, 1, tl1, 1, tlr, 0, tlc, tl);
+        let (x_value, y_value) = (x.value().unwrap(), y.value().unwrap());
+        assert_eq!(x_value, phase((tl1 + tl2 - tl + tlr) / 2) * y_value);
+        assert!((x.value_f64(1.0) - f64::from(x_value)).abs() < 1e-12);
+    }
+    // orthogonality for unequal masses
+    let mut states = Vec::new();
+    for e1 in 0 .. 4 {
+        for n1 in 0 .. e1 / 2 + 1 {
+            for n2 in 0 .. (3 - e1) / 2 + 1 {
+                let (tl1, tl2) = (2 * (e1 - 2 * n1), 2 * (3 - e1 - 2 * n2));
+                if triangle_condition(tl1, tl2, 2) {
+                    states.push((n1, tl1, n2, tl2));
+                }
+            }
+        }
+    }
+    for &tan_beta in &[0.3, 2.5] {
+        let mut memo = Brackets::with_mass_ratio(tan_beta);
+        for &(n1, tl1, n2, tl2) in &states {
+            for &(m1, tm1, m2, tm2) in &states {
+                let dot: f64 = states.iter().map(|&(nr, tlr, nc, tlc)| {
+                    memo.get(b(n1, tl1, n2, tl2, nr, tlr, nc, tlc, 2))
+                        * memo.get(b(m1, tm1, m2, tm2, nr, tlr, nc, tlc, 2))
+                }).sum();
+                let delta = (n1, tl1, n2, tl2) == (m1, tm1, m2, tm2);
+                assert!((dot - f64::from(delta as i32)).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cfp() {
+    use wigner_symbols::cfp::*;
+    use wigner_symbols::slater::shell_direct;
+    assert_eq!(cfp_table(Orbit::J(3), 5), Err(CfpError::Occupancy));
+    // (j^3 j{|j^2 0)² = (2 j − 1) / (3 (2 j + 1))
+    for tj in (1 .. 6).map(|j| 2 * j + 1) {
+        let table = cfp_table(Orbit::J(tj), 3).unwrap();
+        let i = table.find(Term { seniority: 1, tj, ts: 0, index: 0 }).unwrap();
+        assert_eq!(table.cfps[i][0],
+                   SignedSqrt(Rational::from((tj - 1, 3 * (tj + 1)))));
+    }
+    // p³: ⁴S, ²D, ²P have f_2 = −3/5, −6/25, 0
+    let table = cfp_table(Orbit::L(2), 3).unwrap();
+    let terms = table.shell_terms();
+    for &(tl, ts, f) in &[(0, 3, (-9, 25)), (4, 1, (-36, 625)), (2, 1, (0, 1))] {
+        let i = terms.iter().position(|t| (t.tl, t.ts) == (tl, ts)).unwrap();
+        assert_eq!(shell_direct(2, 3, &terms, i, 4),
+                   Some(SignedSqrt(Rational::from(f))));
+    }
+    // number of states, and orthonormality
+    let j_tables = cfp_tables(Orbit::J(9), 10).unwrap();
+    let counts: Vec<usize> = j_tables.iter().map(|t| t.terms.len()).collect();
+    assert_eq!(counts, [1, 5, 10, 18, 20, 18, 10, 5, 1, 1]);
+    let doubled = j_tables[3].terms.iter()
+        .filter(|t| t.seniority == 4 && t.index == 1)
+        .map(|t| t.tj)
+        .collect::<Vec<_>>();
+    assert_eq!(doubled, [8, 12]);
+    let tables = cfp_tables(Orbit::L(4), 10).unwrap();
+    let counts: Vec<usize> = tables.iter().map(|t| t.terms.len()).collect();
+    assert_eq!(counts, [1, 5, 8, 16, 16, 16, 8, 5, 1, 1]);
+    assert_eq!(cfp_table(Orbit::L(6), 3).unwrap().terms.len(), 17);
+    // f^10 is conjugate to f^4, so the f_k of each term differ from those of
+    // f^4 by a constant
+    let holes = cfp_table(Orbit::L(6), 4).unwrap();
+    let f10 = cfp_table(Orbit::L(6), 10).unwrap();
+    assert_eq!(f10.terms, holes.terms);
+    let (terms, hole_terms) = (f10.shell_terms(), holes.shell_terms());
+    for tk in (1 .. 4).map(|k| 4 * k) {
+        let shifts: Vec<_> = (0 .. terms.len()).map(|i| {
+            let f = shell_direct(6, 10, &terms, i, tk).unwrap();
+            let g = shell_direct(6, 4, &hole_terms, i, tk).unwrap();
+            f.checked_add(-1 * g).unwrap()
+        }).collect();
+        assert!(shifts.iter().all(|x| *x == shifts[0]));
+    }
+    for table in tables.iter().chain(&j_tables).chain(Some(&f10)) {
+        for (a, ra) in table.terms.iter().zip(&table.cfps) {
+            for (b, rb) in table.terms.iter().zip(&table.cfps) {
+                if (a.tj, a.ts) != (b.tj, b.ts) {
+                    continue;
+                }
+                let dot = sum_radicals(ra.iter().zip(rb).map(|(x, y)| {
+                    x.clone() * y.clone()
+                }));
+                assert_eq!(dot, Some(SignedSqrt::from((a == b) as i32)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_coupling() {
+    use wigner_symbols::coupling::*;
+    let half = SignedSqrt(Rational::from((1, 2)));
+    // |↑⟩ |↓⟩ = (|1 0⟩ + |0 0⟩) / √2
+    let up = [SignedSqrt::from(0), SignedSqrt::from(1)];
+    let down = [SignedSqrt::from(1), SignedSqrt::from(0)];
+    let state = product(&up, &down);
+    let components = decompose(&Exact, 1, 1, &state).unwrap();
+    assert_eq!(components[&2], [0.into(), half.clone(), 0.into()]);
+    assert_eq!(components[&0], [half]);
+    assert_eq!(couple_back(&Exact, 1, 1, &components), Some(state));
+    // the table agrees with the exact coefficients and the round trip is
+    // the identity
+    let table = ClebschGordanTable::new(7, 0);
+    let (tj1, tj2) = (3, 4);
+    let state: Vec<Vec<f64>> = (0 .. tj1 + 1).map(|i| {
+        (0 .. tj2 + 1).map(|j| ((7 * i + 3 * j) % 11) as f64 - 5.0).collect()
+    }).collect();
+    let exact = decompose(&Exact, tj1, tj2, &state).unwrap();
+    let approx = decompose(&table, tj1, tj2, &state).unwrap();
+    assert_eq!(exact.keys().collect::<Vec<_>>(), [&1, &3, &5, &7]);
+    for (x, y) in exact.values().flatten().zip(approx.values().flatten()) {
+        assert!((x - y).abs() < 1e-12);
+    }
+    let back = couple_back(&table, tj1, tj2, &approx).unwrap();
+    for (x, y) in state.iter().flatten().zip(back.iter().flatten()) {
+        assert!((x - y).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_tesseral() {
+    use wigner_symbols::tesseral::*;
+    // ∫ p_x p_x d_z² dΩ = −1 / √(20π)
+    let gaunt = RealGaunt { tl1: 2, tm1: 2, tl2: 2, tm2: 2, tl3: 4, tm3: 0 };
+    assert_eq!(gaunt.value(), SignedSqrt(Rational::from((-1, 5))));
+    assert!((gaunt.value_f64() + 1.0 / (20.0 * std::f64::consts::PI).sqrt()).abs() < 1e-12);
+    // real harmonics are orthonormal
+    for tl in [0, 2, 4] {
+        for tm1 in get_tms(tl) {
+            for tm2 in get_tms(tl) {
+                let g = RealGaunt { tl1: tl, tm1, tl2: tl, tm2, tl3: 0, tm3: 0 };
+                assert_eq!(g.value(), SignedSqrt::from((tm1 == tm2) as i32));
+            }
+        }
+    }
+    // the real-basis Clebsch-Gordan coefficients are unitary
+    let tl_max = 4;
+    for tl1 in (0 .. tl_max + 1).step_by(2) {
+        for tl2 in (0 .. tl_max + 1).step_by(2) {
+            let coupled: Vec<(i32, i32)> = get_triangular_tjs(tl1 + tl2, tl1, tl2)
+                .flat_map(|tl| get_tms(tl).map(move |tm| (tl, tm)))
+                .collect();
+            for &(tla, tma) in &coupled {
+                for &(tlb, tmb) in &coupled {
+                    let (mut re, mut im) = (0.0, 0.0);
+                    for tm1 in get_tms(tl1) {
+                        for tm2 in get_tms(tl2) {
+                            let cg = |tl12, tm12| RealClebschGordan {
+                                tl1, tm1, tl2, tm2, tl12, tm12,
+                            }.value();
+                            let (a, b) = (cg(tla, tma), cg(tlb, tmb));
+                            let (ar, ai) = (f64::from(a.re()), f64::from(a.im()));
+                            let (br, bi) = (f64::from(b.re()), f64::from(b.im()));
+                            re += ar * br + ai * bi;
+                            im += ar * bi - ai * br;
+                        }
+                    }
+                    let delta = ((tla, tma) == (tlb, tmb)) as i32 as f64;
+                    assert!((re - delta).abs() < 1e-12 && im.abs() < 1e-12);
+                }
+            }
+        }
+    }
+    // (1 1 1; x y z) is proportional to ε_xyz and imaginary
+    let w: tesseral::RealOrImaginary =
+        RealWigner3jm { tl1: 2, tm1: 2, tl2: 2, tm2: -2, tl3: 2, tm3: 0 }.value();
+    assert!(w.imaginary);
+    assert_eq!(w.value.sq(), Rational::from((1, 6)));
+}
+
+#[test]
+fn test_phase_convention() {
+    let conventions = [
+        PhaseConvention::CondonShortley,
+        PhaseConvention::Reversed,
+        PhaseConvention::FanoRacah,
+        PhaseConvention::TimeReversal,
+    ];
+    get_3tjms(6, &mut |w3jm| {
+        let cg = ClebschGordan::from(w3jm);
+        let swapped = ClebschGordan {
+            tj1: cg.tj2,
+            tm1: cg.tm2,
+            tj2: cg.tj1,
+            tm2: cg.tm1,
+            ..cg
+        };
+        let reversed = cg.value_in(PhaseConvention::Reversed);
+        assert_eq!(reversed, swapped.value().into());
+        let w_swapped = Wigner3jm {
+            tj1: w3jm.tj2,
+            tm1: w3jm.tm2,
+            tj2: w3jm.tj1,
+            tm2: w3jm.tm1,
+            ..w3jm
+        };
+        assert_eq!(w3jm.value_in(PhaseConvention::Reversed), w_swapped.value().into());
+        // the coupled state |0 0⟩ is not rephased
+        if cg.tj12 == 0 {
+            assert_eq!(cg.value_in(PhaseConvention::TimeReversal),
+                       RealOrImaginary::i_pow(-cg.tj1) * cg.value().into());
+        }
+        for &a in &conventions {
+            for &b in &conventions {
+                let converted = a.convert_clebsch_gordan(cg, cg.value_in(a), b);
+                assert_eq!(converted, cg.value_in(b));
+                let converted = a.convert_3jm(w3jm, w3jm.value_in(a), b);
+                assert_eq!(converted, w3jm.value_in(b));
+            }
+        }
+    });
+    // V(1 1 1; 1 −1 0) = −(1 1 1; 1 −1 0)
+    let w3jm = Wigner3jm { tj1: 2, tm1: 2, tj2: 2, tm2: -2, tj3: 2, tm3: 0 };
+    assert_eq!(w3jm.value_in(PhaseConvention::FanoRacah),
+               (-1 * w3jm.value()).into());
+}
+
+#[test]
+fn test_qdeformed() {
+    use wigner_symbols::qdeformed::*;
+    // the classical symbols are recovered at q = 1
+    get_3tjms(5, &mut |w3jm| {
+        let x = QWigner3jm::from(w3jm).value(Deformation::Real(1.0));
+        assert!((x.re - f64::from(w3jm.value())).abs() < 1e-12 && x.im == 0.0);
+    });
+    get_6tjs(5, &mut |w6j| {
+        let x = QWigner6j::from(w6j).value(Deformation::Real(1.0));
+        assert!((x - f64::from(w6j.value())).abs() < 1e-12);
+    });
+    // orthonormality of the Clebsch-Gordan coefficients, including at
+    // roots of unity where they are complex
+    for &q in &[Deformation::Real(1.7), Deformation::RootOfUnity(5),
+                Deformation::RootOfUnity(7)] {
+        for (tj1, tj2) in (0 .. 25).map(|i| (i % 5, i / 5)) {
+            let coupled: Vec<(i32, i32)> = get_triangular_tjs(tj1 + tj2, tj1, tj2)
+                .filter(|&tj| q.admissible(tj1, tj2, tj))
+                .flat_map(|tj| get_tms(tj).map(move |tm| (tj, tm)))
+                .collect();
+            for &(tja, tma) in &coupled {
+                for &(tjb, tmb) in &coupled {
+                    let mut dot = Complex::default();
+                    for tm1 in get_tms(tj1) {
+                        for tm2 in get_tms(tj2) {
+                            let cg = |tj12, tm12| QClebschGordan {
+                                tj1, tm1, tj2, tm2, tj12, tm12,
+                            }.value(q);
+                            dot = dot + cg(tja, tma) * cg(tjb, tmb);
+                        }
+                    }
+                    let delta = ((tja, tma) == (tjb, tmb)) as i32 as f64;
+                    assert!((dot.re - delta).abs() < 1e-12 && dot.im.abs() < 1e-12);
+                }
+            }
+        }
+    }
+    // at roots of unity the coefficients are genuinely complex
+    let cg = QClebschGordan { tj1: 1, tm1: 1, tj2: 1, tm2: -1, tj12: 2, tm12: 0 }
+        .value(Deformation::RootOfUnity(7));
+    assert!((cg.re - 0.7262775406438544).abs() < 1e-12);
+    assert!((cg.im + 0.16576810924808666).abs() < 1e-12);
+    // orthogonality of the 6-j symbols, including at roots of unity
+    for &q in &[Deformation::Real(2.3), Deformation::RootOfUnity(5),
+                Deformation::RootOfUnity(7)] {
+        for (a, b, c, d) in (0 .. 6 * 6 * 6 * 6).map(|i| (i % 6, i / 6 % 6, i / 36 % 6, i / 216)) {
+            let xs: Vec<i32> = (0 .. 12)
+                .filter(|&x| q.admissible(a, b, x) && q.admissible(c, d, x))
+                .collect();
+            let ys: Vec<i32> = (0 .. 12)
+                .filter(|&y| q.admissible(a, d, y) && q.admissible(c, b, y))
+                .collect();
+            for &y1 in &ys {
+                for &y2 in &ys {
+                    let dot: f64 = xs.iter().map(|&x| {
+                        let w = |y| QWigner6j {
+                            tj1: a, tj2: b, tj3: x, tj4: c, tj5: d, tj6: y,
+                        }.value(q);
+                        q.number(x + 1) * q.number(y1 + 1) * w(y1) * w(y2)
+                    }).sum();
+                    assert!((dot - f64::from((y1 == y2) as i32)).abs() < 1e-12);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_su3() {
+    use wigner_symbols::su3::*;
+    let irrep = |lambda, mu| Irrep { lambda, mu };
+    let octet = irrep(1, 1);
+    assert_eq!(outer_multiplicity(octet, octet, octet), 2);
+    assert_eq!(outer_multiplicity(octet, octet, irrep(2, 2)), 1);
+    assert_eq!(outer_multiplicity(octet, octet, irrep(3, 0)), 1);
+    assert_eq!(outer_multiplicity(octet, octet, irrep(2, 1)), 0);
+    // isoscalar factors of 8 ⊗ 8 → 1: N, Σ, Λ, Ξ
+    let singlet = Coupling::new(octet, octet, irrep(0, 0));
+    for &((y3, ti), sq) in &[((3, 1), (1, 4)), ((0, 2), (3, 8)), ((0, 0), (1, 8)),
+                             ((-3, 1), (1, 4))] {
+        let f = singlet.isoscalar_factor((y3, ti), (-y3, ti), 0, (0, 0));
+        assert_eq!(f.sq(), Rational::from(sq));
+    }
+    // unitarity and factorization into isoscalar factors
+    for &(a, b) in &[(irrep(1, 0), irrep(1, 0)), (irrep(1, 0), irrep(0, 1)),
+                     (octet, octet), (irrep(2, 0), irrep(1, 1))] {
+        let mut couplings = Vec::new();
+        for lambda in 0 .. 5 {
+            for mu in 0 .. 5 {
+                let c = irrep(lambda, mu);
+                let coupling = Coupling::new(a, b, c);
+                assert_eq!(coupling.multiplicity(), outer_multiplicity(a, b, c));
+                couplings.push(coupling);
+            }
+        }
+        let coupled: Vec<(&Coupling, usize, State)> = couplings.iter()
+            .flat_map(|k| (0 .. k.multiplicity()).flat_map(move |rho| {
+                k.c.states().into_iter().map(move |s| (k, rho, s))
+            }))
+            .collect();
+        assert_eq!(coupled.len(), a.dimension() * b.dimension());
+        for &(k1, rho1, s1) in &coupled {
+            for &(k2, rho2, s2) in &coupled {
+                let mut dot = 0.0;
+                for &sa in &a.states() {
+                    for &sb in &b.states() {
+                        dot += f64::from(k1.clebsch_gordan(sa, sb, rho1, s1))
+                            * f64::from(k2.clebsch_gordan(sa, sb, rho2, s2));
+                    }
+                }
+                let delta = (k1.c, rho1, s1) == (k2.c, rho2, s2);
+                assert!((dot - f64::from(delta as i32)).abs() < 1e-12);
+            }
+        }
+        for &(k, rho, s) in &coupled {
+            for &sa in &a.states() {
+                for &sb in &b.states() {
+                    let iso = k.isoscalar_factor((sa.y3, sa.ti), (sb.y3, sb.ti), rho,
+                                                 (s.y3, s.ti));
+                    let cg = ClebschGordan {
+                        tj1: sa.ti,
+                        tm1: sa.tm,
+                        tj2: sb.ti,
+                        tm2: sb.tm,
+                        tj12: s.ti,
+                        tm12: s.tm,
+                    }.value();
+                    assert_eq!(k.clebsch_gordan(sa, sb, rho, s), iso * cg);
+                }
+            }
+        }
+    }
+    // Elliott basis: (λ, μ) contains Σ (2 L + 1) states
+    for &(lambda, mu) in &[(1, 0), (0, 2), (1, 1), (3, 1), (2, 2), (2, 4)] {
+        let c = irrep(lambda, mu);
+        let count: i32 = c.elliott_states().iter().map(|s| s.tl + 1).sum();
+        assert_eq!(count as usize, c.dimension());
+    }
+    // the L = 1 states of (1, 0) couple to (2, 0) and (0, 1) as in SU(2)
+    let (fund, p) = (irrep(1, 0), ElliottState { tk: 0, tl: 2 });
+    for &(c, tl) in &[(irrep(2, 0), 0), (irrep(2, 0), 4), (irrep(0, 1), 2)] {
+        let k = ElliottCoupling::new(fund, fund, c);
+        assert_eq!(k.reduced(p, p, 0, ElliottState { tk: 0, tl }).sq(), 1);
+    }
+    // orthonormality of the reduced coefficients at each L
+    let couplings: Vec<ElliottCoupling> = [(0, 0), (1, 1), (3, 0), (0, 3), (2, 2)]
+        .iter()
+        .map(|&(lambda, mu)| ElliottCoupling::new(octet, octet, irrep(lambda, mu)))
+        .collect();
+    let coupled: Vec<(&ElliottCoupling, usize, ElliottState)> = couplings.iter()
+        .flat_map(|k| (0 .. k.multiplicity()).flat_map(move |rho| {
+            k.c.elliott_states().into_iter().map(move |s| (k, rho, s))
+        }))
+        .collect();
+    for &(k1, rho1, s1) in &coupled {
+        for &(k2, rho2, s2) in coupled.iter().filter(|c| c.2.tl == s1.tl) {
+            let mut dot = 0.0;
+            for &sa in &octet.elliott_states() {
+                for &sb in &octet.elliott_states() {
+                    dot += f64::from(k1.reduced(sa, sb, rho1, s1))
+                        * f64::from(k2.reduced(sa, sb, rho2, s2));
+                }
+            }
+            let delta = (k1.c, rho1, s1) == (k2.c, rho2, s2);
+            assert!((dot - f64::from(delta as i32)).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn test_spin_network() {
+    use wigner_symbols::spin_network::*;
+    assert_eq!(loop_value(1), Rational::from(-2));
+    // removing a trivial edge leaves a loop
+    for tj in 0 .. 6 {
+        assert_eq!(theta(tj, tj, 0), loop_value(tj));
+    }
+    assert_eq!(theta(2, 2, 2), Rational::from(-3));
+    // relation to the 6-j symbol
+    get_6tjs(6, &mut |w6j| {
+        let Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 } = w6j;
+        let tet = Tetrahedron { tj1, tj2, tj3, tj4, tj5, tj6 }.value();
+        let thetas = theta(tj1, tj2, tj3) * theta(tj1, tj5, tj6)
+            * theta(tj4, tj2, tj6) * theta(tj4, tj5, tj3);
+        assert_eq!(SignedSqrt(tet.clone() * tet.abs()),
+                   SignedSqrt(thetas.abs()) * w6j.value());
+    });
+    // a 4-valent intertwiner in the trivial channel factorizes into loops
+    let norm = Intertwiner { tj1: 3, tj2: 3, tj3: 2, tj4: 2, tj: 0 }.norm();
+    assert_eq!(norm, loop_value(3) * loop_value(2));
+    let norm = Intertwiner { tj1: 1, tj2: 1, tj3: 1, tj4: 1, tj: 2 }.norm();
+    assert_eq!(norm, theta(1, 1, 2).square() / loop_value(2));
+}
+
+#[test]
+fn test_semiclassical() {
+    use wigner_symbols::semiclassical::*;
+    // compare against the exact values along a row, relative to the
+    // amplitude of the oscillations
+    let max_error = |values: Vec<(f64, f64)>| {
+        let amplitude = values.iter().map(|&(e, _)| e.abs()).fold(0.0, f64::max);
+        values.iter().map(|&(e, a)| (e - a).abs()).fold(0.0, f64::max) / amplitude
+    };
+    let row = |tj1, tj2, tj3, tj4, tj5| {
+        (0 .. 300).map(|tj6| {
+            let w6j = Wigner6j { tj1, tj2, tj3, tj4, tj5, tj6 };
+            (f64::from(w6j.value()), w6j.asymptotic_f64())
+        }).filter(|&(e, _)| e != 0.0).collect()
+    };
+    // including the caustics and the forbidden regions
+    assert!(max_error(row(40, 44, 36, 42, 38)) < 0.1);
+    assert!(max_error(row(120, 100, 80, 110, 90)) < 0.01);
+    // a caustic at which K = Σ[cos θ_i < 0] J_i is a half-integer
+    let tjs = [68, 43, 27, 89, 112, 86];
+    let tet = Tetrahedron::from(Wigner6j {
+        tj1: tjs[0], tj2: tjs[1], tj3: tjs[2], tj4: tjs[3], tj5: tjs[4], tj6: tjs[5],
+    });
+    let tk: i32 = tet.dihedral_cosines().iter().zip(&tjs)
+        .filter(|&(&c, _)| c < 0.0)
+        .map(|(_, &tj)| tj + 1)
+        .sum();
+    assert_eq!(tk % 2, 1);
+    assert!(max_error(row(68, 43, 27, 89, 112)) < 0.02);
+
+    let w6j = Wigner6j { tj1: 40, tj2: 44, tj3: 36, tj4: 42, tj5: 38, tj6: 30 };
+    assert!(Tetrahedron::from(w6j).volume_sq() > 0.0);
+    let w6j = Wigner6j { tj1: 40, tj2: 44, tj3: 36, tj4: 42, tj5: 38, tj6: 80 };
+    assert!(Tetrahedron::from(w6j).volume_sq() < 0.0);
+
+    // deep inside the classically allowed region
+    let mut values = Vec::new();
+    for tm1 in (-40 .. 41).step_by(4) {
+        for tm2 in (-40 .. 41).step_by(4) {
+            let w3jm = Wigner3jm {
+                tj1: 100, tm1, tj2: 100, tm2, tj3: 100, tm3: -tm1 - tm2,
+            };
+            assert!(ProjectedTriangle::from(w3jm).area_sq() > 0.0);
+            values.push((f64::from(w3jm.value()), w3jm.asymptotic_f64()));
+        }
+    }
+    assert!(max_error(values) < 0.02);
+
+    // across the boundary of the region, with half-integer j1 and j3
+    for &(tj1, tj2, tj3, tm3) in &[(200, 150, 90, 40), (101, 80, 61, 7)] {
+        let values = (-tj1 .. tj1 + 1).step_by(2).map(|tm1| {
+            let w3jm = Wigner3jm { tj1, tm1, tj2, tm2: -tm1 - tm3, tj3, tm3 };
+            (f64::from(w3jm.value()), w3jm.asymptotic_f64())
+        }).filter(|&(e, _)| e != 0.0).collect();
+        assert!(max_error(values) < 0.02);
+    }
+
+    let mut values = Vec::new();
+    for tm1 in (-201 .. 202).step_by(2) {
+        for tm3 in (-3 .. 4).step_by(2) {
+            let w3jm = Wigner3jm {
+                tj1: 201, tm1, tj2: 200, tm2: -tm1 - tm3, tj3: 3, tm3,
+            };
+            values.push((f64::from(w3jm.value()), wigner_3jm_edmonds(w3jm)));
+        }
+    }
+    assert!(max_error(values) < 0.01);
+}
+
+#[test]
+fn test_classify() {
+    use wigner_symbols::semiclassical::*;
+    let region_6j = |tj6| {
+        classify_6j(Wigner6j { tj1: 40, tj2: 44, tj3: 36, tj4: 42, tj5: 38, tj6 })
+            .unwrap().region
+    };
+    assert_eq!(region_6j(30), Region::Allowed);
+    assert_eq!(region_6j(74), Region::TurningPoint);
+    assert_eq!(region_6j(76), Region::TurningPoint);
+    assert_eq!(region_6j(78), Region::Forbidden);
+    let w6j = Wigner6j { tj1: 40, tj2: 44, tj3: 36, tj4: 42, tj5: 38, tj6: 76 };
+    let c = classify_6j(w6j).unwrap();
+    assert!(c.measure_sq < 0.0 && c.airy_argument > 0.0);
+    assert_eq!(c.measure_sq, Tetrahedron::from(w6j).volume_sq());
+    assert_eq!(classify_6j(Wigner6j { tj6: 1, ..w6j }), None);
+
+    let region_3jm = |tm1| {
+        classify_3jm(Wigner3jm {
+            tj1: 100, tm1, tj2: 100, tm2: 20, tj3: 100, tm3: -tm1 - 20,
+        }).unwrap().region
+    };
+    assert_eq!(region_3jm(-100), Region::Forbidden);
+    assert_eq!(region_3jm(-96), Region::TurningPoint);
+    assert_eq!(region_3jm(-94), Region::TurningPoint);
+    assert_eq!(region_3jm(-80), Region::Allowed);
+
+    // the Ponzano–Regge formula is good away from the turning points
+    for tm1 in (-60 .. 61).step_by(2) {
+        for tm2 in (-60 .. 61).step_by(2) {
+            let w3jm = Wigner3jm {
+                tj1: 60, tm1, tj2: 50, tm2, tj3: 40, tm3: -tm1 - tm2,
+            };
+            if let Some(c) = classify_3jm(w3jm) {
+                let error = (f64::from(w3jm.value()) - w3jm.asymptotic_f64()).abs();
+                match c.region {
+                    Region::Allowed => assert!(error < 0.01),
+                    Region::Forbidden => assert!(f64::from(w3jm.value()).abs() < 0.01),
+                    Region::TurningPoint => {}
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hyperspherical() {
+    use wigner_symbols::hyperspherical::HypersphericalCoupling;
+    let cg = |tj1, tm1, tj2, tm2, tj12, tm12| f64::from(ClebschGordan {
+        tj1, tm1, tj2, tm2, tj12, tm12,
+    }.value());
+    // the harmonic of degree zero is a scalar
+    let k = HypersphericalCoupling {
+        n1: 1, tl1: 0, tm1: 0, n2: 3, tl2: 4, tm2: 2, n: 3, tl: 4, tm: 2,
+    };
+    assert_eq!(k.value(), 1.into());
+    // compare with the coupling of the two SU(2) factors
+    for n1 in 1 .. 4 {
+        for n2 in 1 .. 4 {
+            for n in 1 .. 6 {
+                let (tj1, tj2, tj) = (n1 - 1, n2 - 1, n - 1);
+                for tl1 in (0 .. 2 * tj1 + 1).step_by(2) {
+                    for tl2 in (0 .. 2 * tj2 + 1).step_by(2) {
+                        for tl in (0 .. 2 * tj + 1).step_by(2) {
+                            for tm1 in get_tms(tl1) {
+                                for tm2 in get_tms(tl2) {
+                                    let tm = tm1 + tm2;
+                                    let mut sum = 0.0;
+                                    for ta1 in get_tms(tj1) {
+                                        for ta2 in get_tms(tj2) {
+                                            let (tb1, tb2) = (tm1 - ta1, tm2 - ta2);
+                                            sum += cg(tj1, ta1, tj1, tb1, tl1, tm1)
+                                                * cg(tj2, ta2, tj2, tb2, tl2, tm2)
+                                                * cg(tj1, ta1, tj2, ta2, tj, ta1 + ta2)
+                                                * cg(tj1, tb1, tj2, tb2, tj, tb1 + tb2)
+                                                * cg(tj, ta1 + ta2, tj, tb1 + tb2, tl, tm);
+                                        }
+                                    }
+                                    let k = HypersphericalCoupling {
+                                        n1, tl1, tm1, n2, tl2, tm2, n, tl, tm,
+                                    };
+                                    assert!((f64::from(k.value()) - sum).abs() < 1e-12);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dirac() {
+    use wigner_symbols::dirac::*;
+    use wigner_symbols::tensor::{self, Convention, WignerEckart};
+    assert_eq!(Kappa::new(0, 1), Some(Kappa(-1)));
+    assert_eq!(Kappa::new(2, 1), Some(Kappa(1)));
+    assert_eq!(Kappa::new(2, 3), Some(Kappa(-2)));
+    assert_eq!(Kappa::new(2, 5), None);
+    assert_eq!(Kappa(-2).tl_small(), 4);
+    assert_eq!(Kappa(1).tl_small(), 0);
+    // ⟨s1/2‖C^1‖p1/2⟩ = −√(2/3) and parity forbids ⟨s1/2‖C^1‖s1/2⟩
+    assert_eq!(racah_tensor(Kappa(-1), 2, Kappa(1)), SignedSqrt(Rational::from((-2, 3))));
+    assert_eq!(racah_tensor(Kappa(-1), 2, Kappa(-1)), SignedSqrt::default());
+
+    // compare with the expansion Ω_κm = Σ ⟨l m−σ ½ σ|j m⟩ Y_l,m−σ χ_σ,
+    // using either component
+    let kappas: Vec<Kappa> = (-4 .. 5).filter(|&k| k != 0).map(Kappa).collect();
+    for &kappa1 in &kappas {
+        for &kappa2 in &kappas {
+            for tk in (0 .. 9).step_by(2) {
+                let small = |k: Kappa| Kappa::new(k.tl_small(), k.tj()).unwrap();
+                for &(k1, k2) in &[(kappa1, kappa2), (small(kappa1), small(kappa2))] {
+                    let (tl1, tl2) = (k1.tl(), k2.tl());
+                    let orbital = WignerEckart {
+                        tj1: tl1,
+                        tk,
+                        tj2: tl2,
+                        convention: Convention::Edmonds,
+                    };
+                    for tm1 in get_tms(k1.tj()) {
+                        for tm2 in get_tms(k2.tj()) {
+                            let tq = tm1 - tm2;
+                            let mut sum = 0.0;
+                            for &ts in &[-1, 1] {
+                                let cg1 = ClebschGordan {
+                                    tj1: tl1, tm1: tm1 - ts, tj2: 1, tm2: ts,
+                                    tj12: k1.tj(), tm12: tm1,
+                                };
+                                let cg2 = ClebschGordan {
+                                    tj1: tl2, tm1: tm2 - ts, tj2: 1, tm2: ts,
+                                    tj12: k2.tj(), tm12: tm2,
+                                };
+                                let c = orbital.matrix_element(
+                                    tensor::racah_tensor(tl1, tk, tl2), tm1 - ts, tq, tm2 - ts);
+                                sum += f64::from(cg1.value() * cg2.value() * c);
+                            }
+                            let value = racah_tensor_element(
+                                kappa1, tm1, tk, tq, kappa2, tm2);
+                            assert!((f64::from(value) - sum).abs() < 1e-12);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_helicity() {
+    use wigner_symbols::helicity::*;
+    // ρ → π π proceeds only in the P wave
+    let rho = HelicityCoupling {
+        tj: 2,
+        ts1: 0,
+        ts2: 0,
+        parities: Some(Parities { parent: -1, first: -1, second: -1 }),
+    };
+    assert_eq!(rho.partial_waves(), vec![(2, 0)]);
+    assert_eq!(rho.matrix(), vec![vec![SignedSqrt::from(1)]]);
+
+    for tj in 0 .. 5 {
+        for ts1 in 0 .. 4 {
+            for ts2 in 0 .. 3 {
+                for &parity in &[1, -1] {
+                    let parities = Parities { parent: parity, first: 1, second: 1 };
+                    for &parities in &[None, Some(parities)] {
+                        let h = HelicityCoupling { tj, ts1, ts2, parities };
+                        let helicities = h.helicities();
+                        let waves = h.partial_waves();
+                        let matrix = h.matrix();
+                        if parities.is_none() && (tj + ts1 + ts2) % 2 == 0 {
+                            assert_eq!(helicities.len(), waves.len());
+                        }
+                        for a in 0 .. waves.len() {
+                            for b in 0 .. waves.len() {
+                                let dot: f64 = matrix.iter()
+                                    .map(|row| f64::from(row[a].clone() * row[b].clone()))
+                                    .sum();
+                                let expected = if a == b { 1.0 } else { 0.0 };
+                                assert!((dot - expected).abs() < 1e-12);
+                            }
+                        }
+                        // parity relates opposite helicities
+                        if parities.is_some() {
+                            let eta = parity * phase((tj - ts1 - ts2) / 2);
+                            for (i, &(tl1, tl2)) in helicities.iter().enumerate() {
+                                let k = helicities.iter()
+                                    .position(|&x| x == (-tl1, -tl2)).unwrap();
+                                for (x, y) in matrix[k].iter().zip(&matrix[i]) {
+                                    assert_eq!(*x, eta * y.clone());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}